@@ -0,0 +1,2770 @@
+//! Validation core shared by the `validate` CLI and the WASM build.
+
+use json_comments::StripComments;
+use regex::Regex;
+use serde::Serialize;
+use serde_json::Value;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fs;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+
+mod spdx;
+#[cfg(target_arch = "wasm32")]
+mod wasm;
+
+/// Most topics GitHub accepts on a repository.
+const GITHUB_MAX_TOPICS: usize = 20;
+/// Longest topic GitHub accepts, in characters.
+const GITHUB_MAX_TOPIC_LENGTH: usize = 50;
+/// Characters of an over-long topic quoted in its error.
+const TOPIC_PREVIEW_LENGTH: usize = 20;
+
+/// How deeply subschemas may nest before validation gives up, unless
+/// `Validator::max_depth` says otherwise.
+pub const DEFAULT_MAX_DEPTH: usize = 64;
+
+/// Parses JSONC text: strips `//` and `/* */` comments and trailing commas,
+/// then parses the rest as JSON.
+pub fn parse_jsonc(content: &str) -> Result<Value, serde_json::Error> {
+    serde_json::from_str(&strip_jsonc(content))
+}
+
+/// Parses JSONC like `parse_jsonc`, but straight from a reader, without
+/// holding the text in memory.
+pub fn parse_jsonc_reader<R: Read>(input: R) -> Result<Value, serde_json::Error> {
+    serde_json::from_reader(BufReader::new(StripTrailingCommas::new(
+        StripComments::new(BufReader::new(input)),
+    )))
+}
+
+/// Turns JSONC text into JSON by blanking out comments and trailing commas.
+/// Both are replaced with spaces, so byte offsets match the input.
+pub fn strip_jsonc(content: &str) -> String {
+    let stripped = StripTrailingCommas::new(StripComments::new(content.as_bytes()));
+    let mut json_str = String::new();
+    BufReader::new(stripped)
+        .read_to_string(&mut json_str)
+        .unwrap();
+    json_str
+}
+
+/// Replaces each comma that only whitespace separates from a closing `}` or
+/// `]` with a space (JSONC allows trailing commas, JSON doesn't). Commas in
+/// strings are left alone. Expects comments to be blanked out already.
+struct StripTrailingCommas<R: Read> {
+    input: BufReader<R>,
+    /// A comma outside a string and the whitespace after it, held until the
+    /// next byte shows whether it trails.
+    pending: Vec<u8>,
+    /// Bytes decided on, not yet read out.
+    ready: VecDeque<u8>,
+    in_string: bool,
+    escaped: bool,
+}
+
+impl<R: Read> StripTrailingCommas<R> {
+    fn new(input: R) -> Self {
+        StripTrailingCommas {
+            input: BufReader::new(input),
+            pending: Vec::new(),
+            ready: VecDeque::new(),
+            in_string: false,
+            escaped: false,
+        }
+    }
+
+    fn push(&mut self, byte: u8) {
+        if !self.pending.is_empty() {
+            if byte.is_ascii_whitespace() {
+                self.pending.push(byte);
+                return;
+            }
+            if byte == b'}' || byte == b']' {
+                self.pending[0] = b' ';
+            }
+            self.ready.extend(self.pending.drain(..));
+        }
+        if self.in_string {
+            match byte {
+                _ if self.escaped => self.escaped = false,
+                b'\\' => self.escaped = true,
+                b'"' => self.in_string = false,
+                _ => {}
+            }
+        } else if byte == b'"' {
+            self.in_string = true;
+        } else if byte == b',' {
+            self.pending.push(byte);
+            return;
+        }
+        self.ready.push_back(byte);
+    }
+}
+
+impl<R: Read> Read for StripTrailingCommas<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        while self.ready.is_empty() {
+            let available = self.input.fill_buf()?;
+            if available.is_empty() {
+                // A comma at the very end doesn't trail anything
+                self.ready.extend(self.pending.drain(..));
+                break;
+            }
+            let chunk = available.to_vec();
+            self.input.consume(chunk.len());
+            for byte in chunk {
+                self.push(byte);
+            }
+        }
+        let count = buf.len().min(self.ready.len());
+        for (slot, byte) in buf.iter_mut().zip(self.ready.drain(..count)) {
+            *slot = byte;
+        }
+        Ok(count)
+    }
+}
+
+/// A validation failure at one location in the data.
+#[derive(Serialize)]
+pub struct ValidationError {
+    /// Data path such as `.maintainers[0][1]`; empty for the root object.
+    pub path: String,
+    /// Schema keyword that produced the error, e.g. `format`.
+    pub keyword: &'static str,
+    /// What went wrong, without the path.
+    pub message: String,
+    /// Subschema the value failed against.
+    #[serde(skip)]
+    pub schema: Value,
+    /// Corrected value, when an obvious fix exists (see `--fix`). For an
+    /// unknown property it is the closest defined property name.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub suggestion: Option<String>,
+    /// The offending key, for errors about an unknown property.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub property: Option<String>,
+    /// Names of the schemas that reported this error, when validating
+    /// against several; shown as a `[name]` prefix.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<String>,
+    /// `Warning` when the failing subschema, or one enclosing it, sets
+    /// `x-severity: "warning"`.
+    pub severity: Severity,
+}
+
+/// A relative path found in an `x-localPath` field. The validator only
+/// records it; checking it on disk is up to the caller.
+pub struct LocalFile {
+    /// Data path of the field.
+    pub path: String,
+    /// The path as written, relative to the validated file.
+    pub value: String,
+    /// Whether the field declares `x-localPath: "image"`.
+    pub image: bool,
+    /// Subschema of the field.
+    pub schema: Value,
+}
+
+/// The canonical spelling of a string field with `x-normalize`.
+pub struct Normalization {
+    /// Data path of the field.
+    pub path: String,
+    /// The value with the field's normalizations applied.
+    pub value: String,
+}
+
+/// A property set under one of the old names its subschema's `x-aliases`
+/// lists.
+pub struct Alias {
+    /// Data path of the object holding the property.
+    pub path: String,
+    /// The old name the data uses.
+    pub alias: String,
+    /// The property's current name.
+    pub name: String,
+}
+
+impl std::fmt::Display for Alias {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(
+            f,
+            "{}: \"{}\" is a deprecated alias for \"{}\"",
+            display_path(&self.path),
+            self.alias,
+            self.name
+        )
+    }
+}
+
+/// Keywords whose value maps arbitrary names to subschemas, so a name like
+/// `default` there is a property rather than a data-holding keyword.
+const SCHEMA_MAPS: &[&str] = &["properties", "patternProperties", "$defs", "definitions"];
+
+/// Normalizations `x-normalize` may list, applied in the order given.
+const NORMALIZATIONS: &[&str] = &["trim", "lowercase", "stripGitSuffix"];
+
+/// Applies the `x-normalize` steps in `steps` to `s`; unknown steps are
+/// skipped (`--test-schema` reports them).
+pub fn normalize(s: &str, steps: &[Value]) -> String {
+    steps
+        .iter()
+        .filter_map(Value::as_str)
+        .fold(s.to_string(), |s, step| match step {
+            "trim" => s.trim().to_string(),
+            "lowercase" => s.to_lowercase(),
+            "stripGitSuffix" => s.strip_suffix(".git").map_or(s.clone(), str::to_string),
+            _ => s,
+        })
+}
+
+/// How seriously a validation failure should be treated.
+#[derive(Clone, Copy, PartialEq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// The JSON Schema draft whose meaning applies to keywords the drafts
+/// disagree on: tuples (draft-07 and 2019-09 array `items` with
+/// `additionalItems`, 2020-12 `prefixItems` with `items`) and property
+/// dependencies (draft-07 `dependencies`, later `dependentRequired` and
+/// `dependentSchemas`).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Dialect {
+    Draft07,
+    Draft2019,
+    Draft2020,
+}
+
+impl Dialect {
+    /// Used when the root schema has no recognized `$schema`.
+    pub const DEFAULT: Dialect = Dialect::Draft2020;
+
+    /// The dialect for a `--json-schema-dialect` name.
+    pub fn from_name(name: &str) -> Option<Self> {
+        match name {
+            "draft-07" => Some(Dialect::Draft07),
+            "2019-09" => Some(Dialect::Draft2019),
+            "2020-12" => Some(Dialect::Draft2020),
+            _ => None,
+        }
+    }
+
+    /// The dialect a `$schema` URI names, such as
+    /// `https://json-schema.org/draft/2020-12/schema`.
+    pub fn from_uri(uri: &str) -> Option<Self> {
+        if uri.contains("/draft-07/") {
+            Some(Dialect::Draft07)
+        } else if uri.contains("/draft/2019-09/") {
+            Some(Dialect::Draft2019)
+        } else if uri.contains("/draft/2020-12/") {
+            Some(Dialect::Draft2020)
+        } else {
+            None
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Dialect::Draft07 => "draft-07",
+            Dialect::Draft2019 => "2019-09",
+            Dialect::Draft2020 => "2020-12",
+        }
+    }
+}
+
+impl std::fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(source) = &self.source {
+            write!(f, "[{}] ", source)?;
+        }
+        write!(f, "{}: {}", display_path(&self.path), self.message)?;
+        if let Some(suggestion) = &self.suggestion {
+            write!(f, " (suggested: {})", suggestion)?;
+        }
+        Ok(())
+    }
+}
+
+/// Validation state threaded through the recursion.
+pub struct Validator<'a> {
+    /// Root schema, the target of `#/...` refs outside external files.
+    root: &'a Value,
+    /// Directory that relative external `$ref`s resolve against.
+    schema_dir: PathBuf,
+    /// External schema files loaded so far, keyed by resolved path.
+    documents: HashMap<PathBuf, Value>,
+    /// External file the schema being validated came from, if any.
+    current_document: Option<PathBuf>,
+    /// Per document (`None` for the root), the JSON pointer of each
+    /// subschema named by an `$id` or `$anchor`; see `index_anchors`.
+    anchors: HashMap<Option<PathBuf>, HashMap<String, String>>,
+    /// `(data path, ref)` pairs currently being followed, to detect cycles.
+    active_refs: Vec<(String, String)>,
+    pub errors: Vec<ValidationError>,
+    pub warnings: Vec<String>,
+    /// Paths of fields whose subschema is `readOnly`.
+    pub read_only: Vec<String>,
+    /// Relative paths in `x-localPath` fields, in traversal order.
+    pub local_files: Vec<LocalFile>,
+    /// `x-normalize` fields whose value isn't yet normalized.
+    pub normalized: Vec<Normalization>,
+    /// Properties set under an `x-aliases` name, in traversal order.
+    pub aliases: Vec<Alias>,
+    /// Warn about `readOnly` fields instead of only recording them.
+    pub no_edit_readonly: bool,
+    /// Apply the root schema's cross-field extensions.
+    pub check_cross_fields: bool,
+    /// Warn when a property listed in `required` holds a blank string.
+    pub warn_empty_strings: bool,
+    /// Warn about strings equal to one of these, ignoring case and
+    /// surrounding whitespace; empty to skip the check.
+    pub placeholders: Vec<String>,
+    /// List each allowed value with its `x-enumDescriptions` entry in enum
+    /// errors.
+    pub describe_enums: bool,
+    /// Stop validating at the first error.
+    pub fail_fast: bool,
+    /// Draft semantics for `items` and dependencies; `new` takes it from
+    /// the root schema's `$schema`.
+    pub dialect: Dialect,
+    /// Most nested `validate_property` calls, counting each `$ref` and
+    /// combinator branch as well as each level of data.
+    pub max_depth: usize,
+    /// Current nesting of `validate_property` calls.
+    depth: usize,
+    /// Set once `fail_fast` has seen its error; validation then unwinds.
+    halted: bool,
+    /// Depth of enclosing `x-severity: "warning"` subschemas, whose errors
+    /// don't halt `fail_fast`.
+    warning_scopes: usize,
+    /// What the most recent `validate_property` call evaluated, consumed
+    /// by `unevaluatedProperties` and `unevaluatedItems`.
+    last_evaluated: Evaluated,
+}
+
+/// Lengths of the validator's results before a trial run; see
+/// `Validator::checkpoint`.
+struct Checkpoint {
+    errors: usize,
+    warnings: usize,
+    read_only: usize,
+    local_files: usize,
+    normalized: usize,
+    aliases: usize,
+    fail_fast: bool,
+}
+
+/// Object properties and array items a schema evaluated.
+#[derive(Default)]
+struct Evaluated {
+    properties: HashSet<String>,
+    /// Count of leading array items evaluated.
+    items: usize,
+}
+
+impl Evaluated {
+    fn merge(&mut self, other: Evaluated) {
+        self.properties.extend(other.properties);
+        self.items = self.items.max(other.items);
+    }
+}
+
+impl ValidationError {
+    /// An error-severity error with no subschema, suggestion or source.
+    pub fn new(path: &str, keyword: &'static str, message: String) -> Self {
+        ValidationError {
+            path: path.to_string(),
+            keyword,
+            message,
+            schema: Value::Null,
+            suggestion: None,
+            property: None,
+            source: None,
+            severity: Severity::Error,
+        }
+    }
+}
+
+impl<'a> Validator<'a> {
+    pub fn new(root: &'a Value, schema_dir: &Path) -> Self {
+        let mut root_anchors = HashMap::new();
+        index_anchors(root, "", &mut root_anchors);
+        Validator {
+            root,
+            schema_dir: schema_dir.to_path_buf(),
+            documents: HashMap::new(),
+            current_document: None,
+            anchors: HashMap::from([(None, root_anchors)]),
+            active_refs: Vec::new(),
+            errors: Vec::new(),
+            warnings: Vec::new(),
+            read_only: Vec::new(),
+            local_files: Vec::new(),
+            normalized: Vec::new(),
+            aliases: Vec::new(),
+            no_edit_readonly: false,
+            check_cross_fields: false,
+            warn_empty_strings: false,
+            placeholders: Vec::new(),
+            describe_enums: false,
+            fail_fast: false,
+            dialect: root
+                .get("$schema")
+                .and_then(|s| s.as_str())
+                .and_then(Dialect::from_uri)
+                .unwrap_or(Dialect::DEFAULT),
+            max_depth: DEFAULT_MAX_DEPTH,
+            depth: 0,
+            halted: false,
+            warning_scopes: 0,
+            last_evaluated: Evaluated::default(),
+        }
+    }
+
+    fn report(&mut self, path: &str, keyword: &'static str, schema: &Value, message: String) {
+        self.report_with_suggestion(path, keyword, schema, message, None);
+    }
+
+    fn report_with_suggestion(
+        &mut self,
+        path: &str,
+        keyword: &'static str,
+        schema: &Value,
+        message: String,
+        suggestion: Option<String>,
+    ) {
+        self.push(ValidationError {
+            schema: schema.clone(),
+            suggestion,
+            ..ValidationError::new(path, keyword, message)
+        });
+    }
+
+    /// Reports `key` as a property the schema doesn't allow on the object
+    /// at `path`, suggesting the closest of `defined` if one is near.
+    fn report_unknown_property<'k>(
+        &mut self,
+        path: &str,
+        keyword: &'static str,
+        schema: &Value,
+        key: &str,
+        defined: impl Iterator<Item = &'k str>,
+    ) {
+        let message = if keyword == "unevaluatedProperties" {
+            format!("unevaluated property \"{}\"", key)
+        } else {
+            format!("unknown property \"{}\"", key)
+        };
+        self.push(ValidationError {
+            schema: schema.clone(),
+            suggestion: closest_name(key, defined).map(str::to_string),
+            property: Some(key.to_string()),
+            ..ValidationError::new(path, keyword, message)
+        });
+    }
+
+    fn push(&mut self, error: ValidationError) {
+        if self.halted {
+            return;
+        }
+        self.errors.push(error);
+        if self.fail_fast && self.warning_scopes == 0 {
+            self.halted = true;
+        }
+    }
+
+    pub fn validate(&mut self, data: &Value) {
+        let schema = self.root;
+
+        // Check if root is an object
+        let Some(data_obj) = data.as_object() else {
+            self.report("", "type", schema, "expected object".to_string());
+            return;
+        };
+
+        // Validate each property
+        self.validate_property("", data, schema);
+
+        if self.check_cross_fields {
+            self.check_distinct(data_obj);
+            self.check_disjoint(data_obj);
+        }
+    }
+
+    /// Validates only the subtree of `data` at JSON `pointer`, against the
+    /// subschema that describes it. Errors keep their full data paths.
+    pub fn validate_only(&mut self, data: &Value, pointer: &str) -> Result<(), String> {
+        let (path, value) =
+            resolve_pointer(data, pointer).ok_or("no value at this pointer in the data")?;
+        let mut schema = self.root.clone();
+        let mut current = data;
+        let tokens = pointer.strip_prefix('/').map(|p| p.split('/'));
+        for token in tokens.into_iter().flatten() {
+            let token = token.replace("~1", "/").replace("~0", "~");
+            schema = self
+                .child_schema(&schema, &token, current.is_array())
+                .ok_or_else(|| format!("no subschema describes \"{}\"", token))?;
+            current = match current {
+                Value::Array(items) => token.parse::<usize>().ok().and_then(|i| items.get(i)),
+                _ => current.get(token.as_str()),
+            }
+            .ok_or("no value at this pointer in the data")?;
+        }
+        self.validate_property(&path, value, &schema);
+        self.current_document = None;
+        Ok(())
+    }
+
+    /// The subschema `schema` applies to member `token` of an object, or
+    /// item `token` of an array, looking through `$ref` and `allOf`. Leaves
+    /// `current_document` at the file the subschema came from.
+    fn child_schema(&mut self, schema: &Value, token: &str, in_array: bool) -> Option<Value> {
+        let found = if in_array {
+            let index: usize = token.parse().ok()?;
+            let (tuple, rest) = self.tuple_keywords(schema);
+            tuple
+                .and_then(|t| t.get(index))
+                .or(rest.filter(|r| r.is_object()))
+        } else {
+            let patterns = schema.get("patternProperties").and_then(|p| p.as_object());
+            let properties = schema.get("properties").and_then(|p| p.as_object());
+            properties
+                .and_then(|p| p.get(token))
+                .or_else(|| {
+                    properties?
+                        .values()
+                        .find(|s| aliases(s).any(|a| a == token))
+                })
+                .or_else(|| {
+                    patterns?
+                        .iter()
+                        .find(|(pattern, _)| Regex::new(pattern).is_ok_and(|re| re.is_match(token)))
+                        .map(|(_, s)| s)
+                })
+                .or(schema.get("additionalProperties").filter(|a| a.is_object()))
+        };
+        if let Some(found) = found {
+            return Some(found.clone());
+        }
+
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            let previous = self.current_document.clone();
+            if let Ok((document, target)) = self.resolve_ref(reference) {
+                self.current_document = document;
+                if let Some(found) = self.child_schema(&target, token, in_array) {
+                    return Some(found);
+                }
+            }
+            self.current_document = previous;
+        }
+        let branches = schema.get("allOf").and_then(|a| a.as_array())?;
+        branches
+            .iter()
+            .find_map(|branch| self.child_schema(branch, token, in_array))
+    }
+
+    /// Warns when fields named together in the root `x-distinct` hold the
+    /// same value. It accepts one group (`["homepage", "repository"]`) or a
+    /// list of groups.
+    fn check_distinct(&mut self, data_obj: &serde_json::Map<String, Value>) {
+        let Some(distinct) = self.root.get("x-distinct") else {
+            return;
+        };
+
+        for fields in field_groups(distinct) {
+            for (i, a) in fields.iter().enumerate() {
+                for b in &fields[i + 1..] {
+                    let (Some(a_value), Some(b_value)) = (data_obj.get(*a), data_obj.get(*b))
+                    else {
+                        continue;
+                    };
+                    if normalize_for_comparison(a_value) == normalize_for_comparison(b_value) {
+                        self.warnings.push(format!(
+                            "root: \"{}\" and \"{}\" should differ but have the same value",
+                            a, b
+                        ));
+                    }
+                }
+            }
+        }
+    }
+
+    /// Warns about elements shared by arrays named together in the root
+    /// `x-disjoint`, which takes groups like `x-distinct`. Elements compare
+    /// as in `x-distinct`, so `Docker` and `docker` overlap.
+    fn check_disjoint(&mut self, data_obj: &serde_json::Map<String, Value>) {
+        let Some(disjoint) = self.root.get("x-disjoint") else {
+            return;
+        };
+
+        for fields in field_groups(disjoint) {
+            for (i, a) in fields.iter().enumerate() {
+                for b in &fields[i + 1..] {
+                    let (Some(a_items), Some(b_items)) = (
+                        data_obj.get(*a).and_then(|v| v.as_array()),
+                        data_obj.get(*b).and_then(|v| v.as_array()),
+                    ) else {
+                        continue;
+                    };
+                    let b_items: Vec<Value> =
+                        b_items.iter().map(normalize_for_comparison).collect();
+                    let mut shared = Vec::new();
+                    for item in a_items {
+                        let normalized = normalize_for_comparison(item);
+                        if b_items.contains(&normalized) && !shared.contains(&normalized) {
+                            shared.push(normalized);
+                            self.warnings.push(format!(
+                                "root: \"{}\" appears in both {} and {}",
+                                enum_key(item),
+                                a,
+                                b
+                            ));
+                        }
+                    }
+                }
+            }
+        }
+    }
+
+    /// Validates `value` at `path` against `schema`. Afterwards
+    /// `last_evaluated` holds what this schema evaluated.
+    fn validate_property(&mut self, path: &str, value: &Value, schema: &Value) {
+        if self.halted {
+            return;
+        }
+        // Deep data or a $ref chain that keeps descending would otherwise
+        // overflow the stack
+        if self.depth >= self.max_depth {
+            self.last_evaluated = Evaluated::default();
+            self.report(
+                path,
+                "maxDepth",
+                schema,
+                "maximum validation depth exceeded".to_string(),
+            );
+            return;
+        }
+        let start = self.errors.len();
+        let downgraded = schema.get("x-severity").and_then(|s| s.as_str()) == Some("warning");
+        self.warning_scopes += usize::from(downgraded);
+        self.depth += 1;
+        self.apply_subschema(path, value, schema);
+        self.depth -= 1;
+        self.warning_scopes -= usize::from(downgraded);
+        // x-severity downgrades everything found beneath this subschema
+        if downgraded {
+            for error in &mut self.errors[start..] {
+                error.severity = Severity::Warning;
+            }
+        }
+    }
+
+    fn apply_subschema(&mut self, path: &str, value: &Value, schema: &Value) {
+        let label = display_path(path);
+        let mut evaluated = Evaluated::default();
+        self.last_evaluated = Evaluated::default();
+
+        // Follow $ref, then keep applying any sibling keywords
+        if let Some(reference) = schema.get("$ref").and_then(|r| r.as_str()) {
+            self.validate_ref(path, value, reference);
+            evaluated.merge(std::mem::take(&mut self.last_evaluated));
+        }
+
+        // Every allOf branch applies to the same value
+        if let Some(branches) = schema.get("allOf").and_then(|a| a.as_array()) {
+            for branch in branches {
+                self.validate_property(path, value, branch);
+                evaluated.merge(std::mem::take(&mut self.last_evaluated));
+            }
+        }
+
+        // if/then/else: only the branch selected by `if` applies
+        if let Some(condition) = schema.get("if") {
+            let branch = if self.passes(path, value, condition) {
+                evaluated.merge(std::mem::take(&mut self.last_evaluated));
+                schema.get("then")
+            } else {
+                schema.get("else")
+            };
+            if let Some(branch) = branch {
+                self.validate_property(path, value, branch);
+                evaluated.merge(std::mem::take(&mut self.last_evaluated));
+            }
+        }
+
+        if value.is_object() {
+            self.validate_dependencies(path, value, schema, &mut evaluated);
+        }
+
+        // oneOf: exactly one branch may match; with x-discriminator, the
+        // branch is picked by the `const` of the named property instead
+        if let Some(branches) = schema.get("oneOf").and_then(|o| o.as_array()) {
+            let branch = match schema.get("x-discriminator").and_then(|d| d.as_str()) {
+                Some(field) => self.discriminated_branch(path, value, schema, branches, field),
+                None => self.only_matching_branch(path, value, schema, branches),
+            };
+            if let Some(branch) = branch {
+                self.validate_property(path, value, branch);
+                evaluated.merge(std::mem::take(&mut self.last_evaluated));
+            }
+        }
+
+        // anyOf: at least one branch must match, and each that does applies
+        if let Some(branches) = schema.get("anyOf").and_then(|a| a.as_array()) {
+            let matching: Vec<&Value> = branches
+                .iter()
+                .filter(|branch| self.passes(path, value, branch))
+                .collect();
+            if matching.is_empty() {
+                self.report(
+                    path,
+                    "anyOf",
+                    schema,
+                    "doesn't match any anyOf branch".to_string(),
+                );
+            }
+            for branch in matching {
+                self.validate_property(path, value, branch);
+                evaluated.merge(std::mem::take(&mut self.last_evaluated));
+            }
+        }
+
+        // Check const
+        if let Some(expected) = schema.get("const") {
+            if !json_equal(value, expected) {
+                self.report(
+                    path,
+                    "const",
+                    schema,
+                    format!("value {} doesn't equal const {}", value, expected),
+                );
+            }
+        }
+
+        // x-deprecatedReplacement names what to use instead
+        if schema.get("deprecated") == Some(&Value::Bool(true)) {
+            let warning = match schema
+                .get("x-deprecatedReplacement")
+                .and_then(|r| r.as_str())
+            {
+                Some(replacement) => format!(
+                    "{}: property is deprecated, use \"{}\" instead",
+                    label, replacement
+                ),
+                None => format!("{}: property is deprecated", label),
+            };
+            if !self.warnings.contains(&warning) {
+                self.warnings.push(warning);
+            }
+        }
+
+        if schema.get("readOnly") == Some(&Value::Bool(true))
+            && !self.read_only.iter().any(|p| p == path)
+        {
+            self.read_only.push(path.to_string());
+            if self.no_edit_readonly {
+                self.warnings.push(format!(
+                    "{}: read-only field should not be set by hand",
+                    label
+                ));
+            }
+        }
+
+        // Check enum; x-caseInsensitive relaxes the match for string members
+        if let Some(members) = schema.get("enum").and_then(|e| e.as_array()) {
+            let ignore_case = schema.get("x-caseInsensitive") == Some(&Value::Bool(true));
+            let matches = |member: &Value| match (member.as_str(), value.as_str()) {
+                (Some(a), Some(b)) if ignore_case => a.to_lowercase() == b.to_lowercase(),
+                _ => json_equal(member, value),
+            };
+            if !members.iter().any(matches) {
+                let descriptions = schema
+                    .get("x-enumDescriptions")
+                    .and_then(|d| d.as_object())
+                    .filter(|_| self.describe_enums);
+                let message = match descriptions {
+                    Some(descriptions) => {
+                        let allowed: Vec<String> = members
+                            .iter()
+                            .map(|member| {
+                                let name = enum_key(member);
+                                match descriptions.get(&name).and_then(|d| d.as_str()) {
+                                    Some(description) => format!("{} ({})", name, description),
+                                    None => name,
+                                }
+                            })
+                            .collect();
+                        format!(
+                            "value {} not in enum; allowed: {}",
+                            value,
+                            allowed.join(", ")
+                        )
+                    }
+                    None => format!(
+                        "value {} not in enum {}",
+                        value,
+                        Value::from(members.clone())
+                    ),
+                };
+                self.report(path, "enum", schema, message);
+            }
+        }
+
+        // A list of types accepts a value of any of them
+        if let Some(types) = schema.get("type").and_then(|t| t.as_array()) {
+            let names: Vec<&str> = types.iter().filter_map(Value::as_str).collect();
+            if !names.iter().any(|name| has_type(value, name)) {
+                self.report(
+                    path,
+                    "type",
+                    schema,
+                    format!("expected {}", names.join(" or ")),
+                );
+            }
+        }
+
+        let expected_type = schema.get("type").and_then(|t| t.as_str());
+
+        match expected_type {
+            Some("string") => {
+                if !value.is_string() {
+                    self.report(path, "type", schema, "expected string".to_string());
+                    return;
+                }
+                let s = value.as_str().unwrap();
+
+                if self
+                    .placeholders
+                    .iter()
+                    .any(|p| p.eq_ignore_ascii_case(s.trim()))
+                {
+                    let warning = format!("{}: looks like a placeholder value", label);
+                    // A value checked against several subschemas warns once
+                    if !self.warnings.contains(&warning) {
+                        self.warnings.push(warning);
+                    }
+                }
+
+                // Check format
+                if let Some(format) = schema.get("format").and_then(|f| f.as_str()) {
+                    match format {
+                        "uri" if !is_valid_uri(s) => {
+                            self.report_with_suggestion(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid URI \"{}\"", s),
+                                suggest_uri(s),
+                            );
+                        }
+                        "email" if !is_valid_email(s) => {
+                            self.report_with_suggestion(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid email \"{}\"", s),
+                                suggest_email(s),
+                            );
+                        }
+                        "idn-email" if !is_valid_idn_email(s) => {
+                            self.report(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid internationalized email \"{}\"", s),
+                            );
+                        }
+                        "spdx" => {
+                            if let Err(reason) = spdx::check_expression(s) {
+                                self.report_with_suggestion(
+                                    path,
+                                    "format",
+                                    schema,
+                                    format!("invalid SPDX expression \"{}\": {}", s, reason),
+                                    spdx::fix_case(s),
+                                );
+                            }
+                        }
+                        "spdx-id" if !spdx::is_license_id(s) => {
+                            let license = spdx::canonical_license(s);
+                            let message = match &license {
+                                Some(license) => format!(
+                                    "unknown SPDX license \"{}\" (did you mean \"{}\"?)",
+                                    s, license
+                                ),
+                                None => format!("unknown SPDX license \"{}\"", s),
+                            };
+                            self.report_with_suggestion(path, "format", schema, message, license);
+                        }
+                        "date" if parse_date(s).is_none() => {
+                            self.report(path, "format", schema, format!("invalid date \"{}\"", s));
+                        }
+                        "uri-template" if !is_valid_uri_template(s) => {
+                            self.report(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid URI template \"{}\"", s),
+                            );
+                        }
+                        "phone" if !is_valid_phone(s) => {
+                            self.report(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid phone number \"{}\"", s),
+                            );
+                        }
+                        "base32" if !is_valid_base32(s) => {
+                            self.report(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid base32 \"{}\"", s),
+                            );
+                        }
+                        "base64url" if !is_valid_base64url(s) => {
+                            self.report(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid base64url \"{}\"", s),
+                            );
+                        }
+                        "git-url" if normalize_git_url(s).is_none() => {
+                            self.report(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid git URL \"{}\"", s),
+                            );
+                        }
+                        "regex" if Regex::new(s).is_err() => {
+                            self.report(path, "format", schema, format!("invalid regex \"{}\"", s));
+                        }
+                        "color" if !is_valid_hex_color(s) => {
+                            self.report(
+                                path,
+                                "format",
+                                schema,
+                                format!("invalid hex color \"{}\"", s),
+                            );
+                        }
+                        _ => {}
+                    }
+                }
+
+                // A JSON string's parsed content must satisfy contentSchema;
+                // content that doesn't parse can't be checked, which only warns
+                let embeds_json = schema.get("contentMediaType").and_then(|m| m.as_str())
+                    == Some("application/json");
+                if let Some(content_schema) = schema.get("contentSchema").filter(|_| embeds_json) {
+                    match serde_json::from_str::<Value>(s) {
+                        Err(e) => {
+                            let warning = format!(
+                                "{}: embedded JSON doesn't parse, skipping contentSchema: {}",
+                                label, e
+                            );
+                            if !self.warnings.contains(&warning) {
+                                self.warnings.push(warning);
+                            }
+                        }
+                        Ok(embedded) => {
+                            for error in self.failures("", &embedded, content_schema) {
+                                self.report(
+                                    path,
+                                    "contentSchema",
+                                    schema,
+                                    format!(
+                                        "embedded JSON invalid: {}: {}",
+                                        display_path(&error.path),
+                                        error.message
+                                    ),
+                                );
+                            }
+                        }
+                    }
+                }
+
+                // Check data URI media type against x-allowedMediaTypes
+                if let Some(allowed) = schema.get("x-allowedMediaTypes").and_then(|a| a.as_array())
+                {
+                    if let Some(subtype) = data_image_subtype(s) {
+                        if !allowed.iter().any(|a| a.as_str() == Some(subtype)) {
+                            self.report(
+                                path,
+                                "x-allowedMediaTypes",
+                                schema,
+                                format!("media type \"{}\" not allowed", subtype),
+                            );
+                        }
+                    }
+                }
+
+                // Check the size of an inline payload against x-maxDecodedBytes
+                if let Some(limit) = schema.get("x-maxDecodedBytes").and_then(|m| m.as_u64()) {
+                    if let Some(message) = oversized_data_uri(s, limit) {
+                        self.report(path, "x-maxDecodedBytes", schema, message);
+                    }
+                }
+
+                // Check funding URLs against x-fundingPlatform's host
+                if let Some(platform) = schema.get("x-fundingPlatform").and_then(|p| p.as_str()) {
+                    match funding_url_prefix(platform) {
+                        Some(prefix) if !is_funding_url(s, prefix) => {
+                            let expected = prefix.trim_end_matches('/');
+                            self.report(
+                                path,
+                                "x-fundingPlatform",
+                                schema,
+                                format!("expected a {} URL", expected),
+                            );
+                        }
+                        // `format: uri` has already reported a bad URI
+                        None if schema.get("format").and_then(|f| f.as_str()) != Some("uri")
+                            && !is_valid_uri(s) =>
+                        {
+                            self.report(path, "format", schema, format!("invalid URI \"{}\"", s));
+                        }
+                        _ => {}
+                    }
+                }
+
+                // Record relative paths for the caller to check on disk
+                if let Some(kind) = schema.get("x-localPath").and_then(|k| k.as_str()) {
+                    if !s.contains("://") && !s.starts_with("data:") {
+                        self.local_files.push(LocalFile {
+                            path: path.to_string(),
+                            value: s.to_string(),
+                            image: kind == "image",
+                            schema: schema.clone(),
+                        });
+                    }
+                }
+
+                // Record values that --normalize would rewrite
+                if let Some(steps) = schema.get("x-normalize").and_then(|n| n.as_array()) {
+                    let value = normalize(s, steps);
+                    if value != *s && !self.normalized.iter().any(|n| n.path == path) {
+                        self.normalized.push(Normalization {
+                            path: path.to_string(),
+                            value,
+                        });
+                    }
+                }
+
+                // Check pattern
+                if let Some(pattern) = schema.get("pattern").and_then(|p| p.as_str()) {
+                    if let Ok(re) = Regex::new(pattern) {
+                        if !re.is_match(s) {
+                            // x-patternDescription says in words what the regex
+                            // means, and x-patternExamples shows values it takes
+                            let mut message =
+                                match schema.get("x-patternDescription").and_then(|d| d.as_str()) {
+                                    Some(description) => format!("must be {}", description),
+                                    None => format!("does not match pattern {}", pattern),
+                                };
+                            let examples: Vec<String> = schema
+                                .get("x-patternExamples")
+                                .and_then(|e| e.as_array())
+                                .into_iter()
+                                .flatten()
+                                .filter(|e| e.is_string())
+                                .map(|e| e.to_string())
+                                .collect();
+                            if !examples.is_empty() {
+                                message.push_str(&format!(" (e.g. {})", examples.join(", ")));
+                            }
+                            self.report(path, "pattern", schema, message);
+                        }
+                    }
+                }
+
+                // Check minLength
+                if let Some(min_len) = schema.get("minLength").and_then(|m| m.as_u64()) {
+                    if (s.chars().count() as u64) < min_len {
+                        self.report(
+                            path,
+                            "minLength",
+                            schema,
+                            format!("string too short (min {})", min_len),
+                        );
+                    }
+                }
+
+                // Check maxLength
+                if let Some(max_len) = schema.get("maxLength").and_then(|m| m.as_u64()) {
+                    if (s.chars().count() as u64) > max_len {
+                        self.report(
+                            path,
+                            "maxLength",
+                            schema,
+                            format!("string too long (max {})", max_len),
+                        );
+                    }
+                }
+            }
+            Some(expected @ ("integer" | "number")) if !is_json_type(value, expected) => {
+                // A quoted number is a common slip, so point at the fix
+                let coerced = value.as_str().and_then(|s| coerce_number(s, expected));
+                let message = match coerced {
+                    Some(number) => format!(
+                        "expected {}, got string {} (did you mean {}?)",
+                        expected, value, number
+                    ),
+                    None => format!("expected {}", expected),
+                };
+                self.report(path, "type", schema, message);
+            }
+            Some("array") if !value.is_array() => {
+                self.report(path, "type", schema, "expected array".to_string());
+                return;
+            }
+            Some("object") if !value.is_object() => {
+                self.report(path, "type", schema, "expected object".to_string());
+            }
+            Some(expected @ ("boolean" | "null")) if !has_type(value, expected) => {
+                self.report(path, "type", schema, format!("expected {}", expected));
+            }
+            _ => {}
+        }
+
+        if value.is_number() {
+            self.validate_bounds(path, value, schema);
+        }
+        if let Some(arr) = value.as_array() {
+            self.validate_array(path, arr, schema, &mut evaluated);
+        }
+        if let Some(obj) = value.as_object() {
+            self.validate_object(path, obj, schema, &mut evaluated);
+        }
+        self.last_evaluated = evaluated;
+    }
+
+    /// Applies `minimum`, `maximum`, `exclusiveMinimum` and
+    /// `exclusiveMaximum` to a number. Every supported dialect gives the
+    /// exclusive bounds as numbers; the draft-04 boolean form is skipped
+    /// here and reported by `test_schema`.
+    fn validate_bounds(&mut self, path: &str, value: &Value, schema: &Value) {
+        let Some(n) = value.as_f64() else {
+            return;
+        };
+        type Within = fn(f64, f64) -> bool;
+        let bounds: [(&'static str, &str, Within); 4] = [
+            ("minimum", "at least", |n, bound| n >= bound),
+            ("exclusiveMinimum", "greater than", |n, bound| n > bound),
+            ("maximum", "at most", |n, bound| n <= bound),
+            ("exclusiveMaximum", "less than", |n, bound| n < bound),
+        ];
+        for (keyword, expected, within) in bounds {
+            let Some(bound) = schema.get(keyword).filter(|b| b.is_number()) else {
+                continue;
+            };
+            if !within(n, bound.as_f64().unwrap()) {
+                self.report(
+                    path,
+                    keyword,
+                    schema,
+                    format!("expected {} {}, got {}", expected, bound, value),
+                );
+            }
+        }
+    }
+
+    /// Applies property dependencies to an object: for each property that's
+    /// present, the properties it requires and the subschema it brings in.
+    /// Draft-07 gives both in `dependencies`, as a list of names or a
+    /// subschema; 2019-09 and 2020-12 split them into `dependentRequired`
+    /// and `dependentSchemas`, and ignore `dependencies`.
+    fn validate_dependencies(
+        &mut self,
+        path: &str,
+        value: &Value,
+        schema: &Value,
+        evaluated: &mut Evaluated,
+    ) {
+        let Some(obj) = value.as_object() else {
+            return;
+        };
+        let (keyword, dependencies): (&'static str, Vec<(&String, &Value)>) = match self.dialect {
+            Dialect::Draft07 => (
+                "dependencies",
+                schema
+                    .get("dependencies")
+                    .and_then(|d| d.as_object())
+                    .into_iter()
+                    .flatten()
+                    .collect(),
+            ),
+            Dialect::Draft2019 | Dialect::Draft2020 => (
+                "dependentRequired",
+                ["dependentRequired", "dependentSchemas"]
+                    .iter()
+                    .filter_map(|k| schema.get(*k).and_then(|d| d.as_object()))
+                    .flatten()
+                    .collect(),
+            ),
+        };
+        for (name, dependency) in dependencies {
+            if !obj.contains_key(name) {
+                continue;
+            }
+            match dependency {
+                Value::Array(required) => {
+                    for other in required.iter().filter_map(|r| r.as_str()) {
+                        if !obj.contains_key(other) {
+                            self.report(
+                                path,
+                                keyword,
+                                schema,
+                                format!("\"{}\" is required when \"{}\" is set", other, name),
+                            );
+                        }
+                    }
+                }
+                dependency => {
+                    self.validate_property(path, value, dependency);
+                    evaluated.merge(std::mem::take(&mut self.last_evaluated));
+                }
+            }
+        }
+    }
+
+    /// The tuple schemas of `schema`, and the schema for items past them:
+    /// draft-07 and 2019-09 read an array-valued `items` as the tuple and
+    /// `additionalItems` as the rest, ignoring `prefixItems`; 2020-12 reads
+    /// `prefixItems` and `items`. Without `prefixItems`, 2020-12 still
+    /// takes the older array form, which the gitinfo schema itself uses.
+    fn tuple_keywords<'s>(&self, schema: &'s Value) -> (Option<&'s Vec<Value>>, Option<&'s Value>) {
+        let prefix = schema.get("prefixItems").and_then(|p| p.as_array());
+        match (self.dialect, prefix) {
+            (Dialect::Draft2020, Some(prefix)) => {
+                (Some(prefix), schema.get("items").filter(|i| !i.is_array()))
+            }
+            _ => match schema.get("items") {
+                Some(Value::Array(tuple)) => (Some(tuple), schema.get("additionalItems")),
+                items => (None, items),
+            },
+        }
+    }
+
+    /// Applies `prefixItems`, `items`, `additionalItems` and
+    /// `unevaluatedItems` to an array, as `dialect` reads them. `evaluated`
+    /// arrives holding the items covered by `$ref` and combinator branches,
+    /// and gains the ones evaluated here.
+    fn validate_array(
+        &mut self,
+        path: &str,
+        arr: &[Value],
+        schema: &Value,
+        evaluated: &mut Evaluated,
+    ) {
+        let (tuple, rest) = self.tuple_keywords(schema);
+
+        if let Some(items_schemas) = tuple {
+            for (i, item) in arr.iter().enumerate() {
+                if let Some(item_schema) = items_schemas.get(i) {
+                    self.validate_property(&format!("{}[{}]", path, i), item, item_schema);
+                }
+            }
+            evaluated.items = evaluated.items.max(items_schemas.len().min(arr.len()));
+        }
+
+        if let Some(min) = schema.get("minItems").and_then(|m| m.as_u64()) {
+            if (arr.len() as u64) < min {
+                self.report(
+                    path,
+                    "minItems",
+                    schema,
+                    format!("expected at least {} items", min),
+                );
+            }
+        }
+
+        // GitHub caps repository topics at 20; x-githubTopics says so
+        // plainly instead of the generic maxItems message
+        let github_topics = schema.get("x-githubTopics") == Some(&Value::Bool(true));
+        if github_topics && arr.len() > GITHUB_MAX_TOPICS {
+            self.report(
+                path,
+                "x-githubTopics",
+                schema,
+                format!("GitHub allows at most {} topics", GITHUB_MAX_TOPICS),
+            );
+        } else if let Some(max) = schema.get("maxItems").and_then(|m| m.as_u64()) {
+            if (arr.len() as u64) > max {
+                self.report(
+                    path,
+                    "maxItems",
+                    schema,
+                    format!("expected at most {} items", max),
+                );
+            }
+        }
+
+        // ...and each topic at 50 characters; the error quotes only its start
+        if github_topics {
+            for (i, topic) in arr.iter().enumerate() {
+                let Some(topic) = topic.as_str() else {
+                    continue;
+                };
+                if topic.chars().count() > GITHUB_MAX_TOPIC_LENGTH {
+                    let preview: String = topic.chars().take(TOPIC_PREVIEW_LENGTH).collect();
+                    self.report(
+                        &format!("{}[{}]", path, i),
+                        "x-githubTopics",
+                        schema,
+                        format!(
+                            "topic \"{}...\" exceeds {} characters",
+                            preview, GITHUB_MAX_TOPIC_LENGTH
+                        ),
+                    );
+                }
+            }
+        }
+
+        if let Some(rest_schema) = rest {
+            let start = tuple.map_or(0, |t| t.len());
+            for (i, item) in arr.iter().enumerate().skip(start) {
+                if rest_schema == &Value::Bool(false) {
+                    self.report(
+                        &format!("{}[{}]", path, i),
+                        "items",
+                        schema,
+                        "unexpected item".to_string(),
+                    );
+                } else {
+                    self.validate_property(&format!("{}[{}]", path, i), item, rest_schema);
+                }
+            }
+            evaluated.items = arr.len();
+        }
+
+        match schema.get("unevaluatedItems") {
+            Some(Value::Bool(false)) => {
+                for i in evaluated.items..arr.len() {
+                    self.report(
+                        &format!("{}[{}]", path, i),
+                        "unevaluatedItems",
+                        schema,
+                        "unevaluated item".to_string(),
+                    );
+                }
+            }
+            Some(extra @ Value::Object(_)) => {
+                for (i, item) in arr.iter().enumerate().skip(evaluated.items) {
+                    self.validate_property(&format!("{}[{}]", path, i), item, extra);
+                }
+            }
+            _ => {}
+        }
+        if schema.get("unevaluatedItems").is_some() {
+            evaluated.items = arr.len();
+        }
+
+        if let Some(sorted_by) = schema.get("x-sortedBy") {
+            self.check_sorted(path, arr, schema, sorted_by);
+        }
+    }
+
+    /// x-sortedBy: `{"field": ..., "order": "asc" | "desc"}` requires the
+    /// items' `field` values in that order (ascending by default). Dates
+    /// compare as dates, numbers as numbers and other strings as text;
+    /// pairs that can't be compared, such as a missing field, are skipped
+    /// and left to the item schema.
+    fn check_sorted(&mut self, path: &str, arr: &[Value], schema: &Value, sorted_by: &Value) {
+        let Some(field) = sorted_by.get("field").and_then(|f| f.as_str()) else {
+            return;
+        };
+        let order = sorted_by
+            .get("order")
+            .and_then(|o| o.as_str())
+            .unwrap_or("asc");
+        // How each entry should compare with the next; ties are fine
+        let wanted = if order == "desc" {
+            Ordering::Greater
+        } else {
+            Ordering::Less
+        };
+        for i in 1..arr.len() {
+            let (Some(previous), Some(current)) = (arr[i - 1].get(field), arr[i].get(field)) else {
+                continue;
+            };
+            if compare_sort_keys(previous, current) == Some(wanted.reverse()) {
+                self.report(
+                    path,
+                    "x-sortedBy",
+                    schema,
+                    format!(
+                        "entries are not sorted by {} ({}) at index {}",
+                        field, order, i
+                    ),
+                );
+                return;
+            }
+        }
+    }
+
+    /// Applies `additionalProperties`, `properties`, `patternProperties` and
+    /// `unevaluatedProperties` to an object. `evaluated` arrives holding the
+    /// properties covered by `$ref` and combinator branches, and gains the
+    /// ones evaluated here.
+    fn validate_object(
+        &mut self,
+        path: &str,
+        obj: &serde_json::Map<String, Value>,
+        schema: &Value,
+        evaluated: &mut Evaluated,
+    ) {
+        if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
+            let mut allowed: HashSet<&str> = HashSet::new();
+            if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+                allowed.extend(properties.keys().map(|k| k.as_str()));
+                allowed.extend(properties.values().flat_map(aliases));
+            }
+            let patterns: Vec<Regex> = schema
+                .get("patternProperties")
+                .and_then(|p| p.as_object())
+                .map(|p| p.keys().filter_map(|k| Regex::new(k).ok()).collect())
+                .unwrap_or_default();
+            for key in obj.keys() {
+                if !allowed.contains(key.as_str()) && !patterns.iter().any(|re| re.is_match(key)) {
+                    self.report_unknown_property(
+                        path,
+                        "additionalProperties",
+                        schema,
+                        key,
+                        allowed.iter().copied(),
+                    );
+                }
+            }
+        }
+
+        // x-oneOfFields: one group of field names, or a list of groups
+        if let Some(groups) = schema.get("x-oneOfFields") {
+            for fields in field_groups(groups) {
+                let present = fields.iter().filter(|f| obj.contains_key(**f)).count();
+                if present != 1 {
+                    self.report(
+                        path,
+                        "x-oneOfFields",
+                        schema,
+                        format!(
+                            "exactly one of [{}] must be present, found {}",
+                            fields.join(", "),
+                            present
+                        ),
+                    );
+                }
+            }
+        }
+
+        // x-requiredIf: one rule, or a list, each requiring fields when
+        // another field has a given value
+        if let Some(rules) = schema.get("x-requiredIf") {
+            let rules = match rules {
+                Value::Array(rules) => rules.iter().collect(),
+                rule => vec![rule],
+            };
+            for rule in rules {
+                let (Some(field), Some(expected)) = (
+                    rule.get("field").and_then(|f| f.as_str()),
+                    rule.get("equals"),
+                ) else {
+                    continue;
+                };
+                if !obj.get(field).is_some_and(|v| json_equal(v, expected)) {
+                    continue;
+                }
+                let required = rule.get("required").and_then(|r| r.as_array());
+                for name in required.into_iter().flatten().filter_map(|n| n.as_str()) {
+                    if !obj.contains_key(name) {
+                        self.report(
+                            path,
+                            "x-requiredIf",
+                            schema,
+                            format!("\"{}\" is required when {} == {}", name, field, expected),
+                        );
+                    }
+                }
+            }
+        }
+
+        if self.warn_empty_strings {
+            let required = schema.get("required").and_then(|r| r.as_array());
+            for key in required.into_iter().flatten().filter_map(|k| k.as_str()) {
+                if obj
+                    .get(key)
+                    .and_then(|v| v.as_str())
+                    .is_some_and(|s| s.trim().is_empty())
+                {
+                    self.warnings
+                        .push(format!("{}.{}: required string is empty", path, key));
+                }
+            }
+        }
+
+        if let Some(properties) = schema.get("properties").and_then(|p| p.as_object()) {
+            for (key, prop_schema) in properties {
+                if let Some(value) = obj.get(key) {
+                    self.validate_property(&format!("{}.{}", path, key), value, prop_schema);
+                    evaluated.properties.insert(key.clone());
+                }
+                // x-aliases: old names still accepted, with a warning
+                for alias in aliases(prop_schema) {
+                    let Some(value) = obj.get(alias) else {
+                        continue;
+                    };
+                    if obj.contains_key(key) {
+                        self.report(
+                            path,
+                            "x-aliases",
+                            prop_schema,
+                            format!("both \"{}\" and its alias \"{}\" are set", key, alias),
+                        );
+                    }
+                    let renamed = Alias {
+                        path: path.to_string(),
+                        alias: alias.to_string(),
+                        name: key.clone(),
+                    };
+                    let warning = renamed.to_string();
+                    if !self.warnings.contains(&warning) {
+                        self.warnings.push(warning);
+                        self.aliases.push(renamed);
+                    }
+                    self.validate_property(&format!("{}.{}", path, alias), value, prop_schema);
+                    evaluated.properties.insert(alias.to_string());
+                }
+            }
+        }
+
+        if let Some(patterns) = schema.get("patternProperties").and_then(|p| p.as_object()) {
+            for (pattern, prop_schema) in patterns {
+                let Ok(re) = Regex::new(pattern) else {
+                    continue;
+                };
+                for (key, value) in obj.iter().filter(|(key, _)| re.is_match(key)) {
+                    self.validate_property(&format!("{}.{}", path, key), value, prop_schema);
+                    evaluated.properties.insert(key.clone());
+                }
+            }
+        }
+
+        // A schema in additionalProperties applies to the properties that
+        // neither `properties` nor `patternProperties` covers
+        if let Some(extra @ Value::Object(_)) = schema.get("additionalProperties") {
+            let defined = schema.get("properties").and_then(|p| p.as_object());
+            let patterns: Vec<Regex> = schema
+                .get("patternProperties")
+                .and_then(|p| p.as_object())
+                .map(|p| p.keys().filter_map(|k| Regex::new(k).ok()).collect())
+                .unwrap_or_default();
+            let aliased: HashSet<&str> = defined
+                .iter()
+                .flat_map(|d| d.values())
+                .flat_map(aliases)
+                .collect();
+            for (key, value) in obj {
+                if !defined.is_some_and(|d| d.contains_key(key))
+                    && !aliased.contains(key.as_str())
+                    && !patterns.iter().any(|re| re.is_match(key))
+                {
+                    self.validate_property(&format!("{}.{}", path, key), value, extra);
+                }
+            }
+        }
+
+        // additionalProperties evaluates everything left over
+        if schema.get("additionalProperties").is_some() {
+            evaluated.properties.extend(obj.keys().cloned());
+        }
+
+        match schema.get("unevaluatedProperties") {
+            Some(Value::Bool(false)) => {
+                let defined = schema.get("properties").and_then(|p| p.as_object());
+                for key in obj.keys().filter(|k| !evaluated.properties.contains(*k)) {
+                    self.report_unknown_property(
+                        path,
+                        "unevaluatedProperties",
+                        schema,
+                        key,
+                        defined.iter().flat_map(|d| d.keys()).map(|k| k.as_str()),
+                    );
+                }
+            }
+            Some(extra @ Value::Object(_)) => {
+                for (key, value) in obj
+                    .iter()
+                    .filter(|(k, _)| !evaluated.properties.contains(*k))
+                {
+                    self.validate_property(&format!("{}.{}", path, key), value, extra);
+                }
+            }
+            _ => {}
+        }
+        if schema.get("unevaluatedProperties").is_some() {
+            evaluated.properties.extend(obj.keys().cloned());
+        }
+
+        // propertyNames validates each key as a string value. String
+        // keywords only apply under `type: "string"`, which keys always
+        // satisfy, so it is assumed when the schema leaves it out.
+        if let Some(names_schema) = schema.get("propertyNames") {
+            let mut key_schema = names_schema.clone();
+            if let Some(key_obj) = key_schema.as_object_mut() {
+                key_obj
+                    .entry("type")
+                    .or_insert_with(|| Value::from("string"));
+            }
+            for key in obj.keys() {
+                for error in self.failures(path, &Value::String(key.clone()), &key_schema) {
+                    self.report(
+                        path,
+                        "propertyNames",
+                        names_schema,
+                        format!("property name \"{}\" invalid: {}", key, error.message),
+                    );
+                }
+            }
+        }
+    }
+
+    /// Validates `value` against `schema` and returns the errors, leaving no
+    /// trace in the validator's own results.
+    fn failures(&mut self, path: &str, value: &Value, schema: &Value) -> Vec<ValidationError> {
+        let checkpoint = self.checkpoint();
+        self.validate_property(path, value, schema);
+        let failed = self.errors.drain(checkpoint.errors..).collect();
+        self.rewind(checkpoint);
+        failed
+    }
+
+    /// Reports whether `value` satisfies `schema` without recording any of
+    /// the resulting errors or warnings.
+    fn passes(&mut self, path: &str, value: &Value, schema: &Value) -> bool {
+        let checkpoint = self.checkpoint();
+        self.validate_property(path, value, schema);
+        let passed = self.errors.len() == checkpoint.errors;
+        self.rewind(checkpoint);
+        passed
+    }
+
+    /// Starts a trial run for `failures` and `passes`, noting where each
+    /// result list ends. A trial failure is only a probe, so `fail_fast` is
+    /// off until `rewind`.
+    fn checkpoint(&mut self) -> Checkpoint {
+        Checkpoint {
+            errors: self.errors.len(),
+            warnings: self.warnings.len(),
+            read_only: self.read_only.len(),
+            local_files: self.local_files.len(),
+            normalized: self.normalized.len(),
+            aliases: self.aliases.len(),
+            fail_fast: std::mem::replace(&mut self.fail_fast, false),
+        }
+    }
+
+    /// Ends a trial run, dropping everything it added to the results.
+    fn rewind(&mut self, checkpoint: Checkpoint) {
+        self.errors.truncate(checkpoint.errors);
+        self.warnings.truncate(checkpoint.warnings);
+        self.read_only.truncate(checkpoint.read_only);
+        self.local_files.truncate(checkpoint.local_files);
+        self.normalized.truncate(checkpoint.normalized);
+        self.aliases.truncate(checkpoint.aliases);
+        self.fail_fast = checkpoint.fail_fast;
+    }
+
+    /// Returns the one `oneOf` branch `value` matches, reporting an error
+    /// when none or several do.
+    fn only_matching_branch<'s>(
+        &mut self,
+        path: &str,
+        value: &Value,
+        schema: &Value,
+        branches: &'s [Value],
+    ) -> Option<&'s Value> {
+        let matching: Vec<&Value> = branches
+            .iter()
+            .filter(|branch| self.passes(path, value, branch))
+            .collect();
+        match matching.as_slice() {
+            [branch] => Some(branch),
+            [] => {
+                self.report(
+                    path,
+                    "oneOf",
+                    schema,
+                    "doesn't match any oneOf branch".to_string(),
+                );
+                None
+            }
+            _ => {
+                self.report(
+                    path,
+                    "oneOf",
+                    schema,
+                    format!("matches {} oneOf branches, expected one", matching.len()),
+                );
+                None
+            }
+        }
+    }
+
+    /// Returns the `oneOf` branch whose `properties.<field>.const` equals the
+    /// value's `field`, reporting an error when there's no such branch.
+    fn discriminated_branch<'s>(
+        &mut self,
+        path: &str,
+        value: &Value,
+        schema: &Value,
+        branches: &'s [Value],
+        field: &str,
+    ) -> Option<&'s Value> {
+        // Non-objects can't carry a discriminator; the type check reports them
+        let obj = value.as_object()?;
+        let Some(tag) = obj.get(field) else {
+            self.report(
+                path,
+                "x-discriminator",
+                schema,
+                format!("missing discriminator \"{}\"", field),
+            );
+            return None;
+        };
+        let branch = branches
+            .iter()
+            .find(|branch| self.branch_tag(branch, field).as_ref() == Some(tag));
+        if branch.is_none() {
+            let message = match tag.as_str() {
+                Some(tag) => format!("unknown discriminator \"{}\"", tag),
+                None => format!("unknown discriminator {}", tag),
+            };
+            self.report(
+                &format!("{}.{}", path, field),
+                "x-discriminator",
+                schema,
+                message,
+            );
+        }
+        branch
+    }
+
+    /// The `const` a `oneOf` branch requires of `field`, looking through a
+    /// `$ref` branch.
+    fn branch_tag(&mut self, branch: &Value, field: &str) -> Option<Value> {
+        let tag = |schema: &Value| schema.get("properties")?.get(field)?.get("const").cloned();
+        if let Some(tag) = tag(branch) {
+            return Some(tag);
+        }
+        let reference = branch.get("$ref")?.as_str()?;
+        let (_, target) = self.resolve_ref(reference).ok()?;
+        tag(&target)
+    }
+
+    /// Validates `value` against the target of `reference`: a local pointer
+    /// (`#/$defs/url`) or anchor (`#url`), a subschema's `$id`, or a relative
+    /// file with an optional pointer or anchor (`common.json#/$defs/url`).
+    fn validate_ref(&mut self, path: &str, value: &Value, reference: &str) {
+        let (document, target) = match self.resolve_ref(reference) {
+            Ok(resolved) => resolved,
+            Err(e) => {
+                self.report(path, "$ref", &Value::Null, e);
+                return;
+            }
+        };
+
+        // A ref re-entered at the same data path can never make progress
+        let pointer = reference.split_once('#').map_or("", |(_, p)| p);
+        let key = match &document {
+            Some(doc) => format!("{}#{}", doc.display(), pointer),
+            None => format!("#{}", pointer),
+        };
+        let frame = (path.to_string(), key);
+        if self.active_refs.contains(&frame) {
+            self.report(
+                path,
+                "$ref",
+                &Value::Null,
+                format!("circular $ref \"{}\"", reference),
+            );
+            return;
+        }
+
+        self.active_refs.push(frame);
+        let previous = std::mem::replace(&mut self.current_document, document);
+        self.validate_property(path, value, &target);
+        self.current_document = previous;
+        self.active_refs.pop();
+    }
+
+    /// Resolves `reference` to the external file it lives in (`None` for the
+    /// root schema) and the target subschema, loading the file if needed.
+    fn resolve_ref(&mut self, reference: &str) -> Result<(Option<PathBuf>, Value), String> {
+        let (file, fragment) = reference.split_once('#').unwrap_or((reference, ""));
+
+        // A subschema's `$id` takes precedence over a file of that name
+        let id_pointer = self
+            .anchors
+            .get(&self.current_document)
+            .and_then(|index| index.get(file))
+            .cloned();
+        let (document, base) = if let Some(id_pointer) = id_pointer {
+            (self.current_document.clone(), id_pointer)
+        } else if file.is_empty() {
+            (self.current_document.clone(), String::new())
+        } else {
+            let base = match &self.current_document {
+                Some(doc) => doc.parent().unwrap_or(&self.schema_dir).to_path_buf(),
+                None => self.schema_dir.clone(),
+            };
+            let resolved = base.join(file);
+            if !self.documents.contains_key(&resolved) {
+                let loaded = fs::read_to_string(&resolved)
+                    .ok()
+                    .and_then(|c| serde_json::from_str::<Value>(&c).ok())
+                    .ok_or_else(|| format!("cannot load $ref target \"{}\"", file))?;
+                let mut index = HashMap::new();
+                index_anchors(&loaded, "", &mut index);
+                self.anchors.insert(Some(resolved.clone()), index);
+                self.documents.insert(resolved.clone(), loaded);
+            }
+            (Some(resolved), String::new())
+        };
+
+        // Anything but a JSON pointer after `#` names an anchor
+        let pointer = if fragment.is_empty() || fragment.starts_with('/') {
+            format!("{}{}", base, fragment)
+        } else {
+            let anchor = format!("#{}", fragment);
+            match self.anchors.get(&document).and_then(|i| i.get(&anchor)) {
+                Some(pointer) => pointer.clone(),
+                None => {
+                    return Err(format!(
+                        "unknown anchor \"{}\" in $ref \"{}\"",
+                        fragment, reference
+                    ))
+                }
+            }
+        };
+        let pointer = pointer.as_str();
+
+        let target = match &document {
+            Some(doc) => self.documents[doc].pointer(pointer),
+            None => self.root.pointer(pointer),
+        };
+        match target {
+            Some(t) => Ok((document, t.clone())),
+            None => Err(format!("cannot resolve $ref \"{}\"", reference)),
+        }
+    }
+}
+
+/// Records where each `$anchor` and `$id` in a schema document points:
+/// `$anchor: "url"` and `$id: "#url"` are indexed as `#url`, any other
+/// `$id` as written (without a trailing `#`). Values are JSON pointers.
+fn index_anchors(node: &Value, pointer: &str, index: &mut HashMap<String, String>) {
+    match node {
+        Value::Object(obj) => {
+            if let Some(anchor) = obj.get("$anchor").and_then(|a| a.as_str()) {
+                index.insert(format!("#{}", anchor), pointer.to_string());
+            }
+            if let Some(id) = obj.get("$id").and_then(|i| i.as_str()) {
+                let id = if id.starts_with('#') {
+                    id
+                } else {
+                    id.trim_end_matches('#')
+                };
+                index.insert(id.to_string(), pointer.to_string());
+            }
+            for (key, child) in obj {
+                // These hold data, not subschemas
+                if matches!(key.as_str(), "const" | "enum" | "default" | "examples") {
+                    continue;
+                }
+                let child_pointer =
+                    format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+                match child {
+                    Value::Object(map) if SCHEMA_MAPS.contains(&key.as_str()) => {
+                        for (name, schema) in map {
+                            let name = name.replace('~', "~0").replace('/', "~1");
+                            index_anchors(schema, &format!("{}/{}", child_pointer, name), index);
+                        }
+                    }
+                    _ => index_anchors(child, &child_pointer, index),
+                }
+            }
+        }
+        Value::Array(arr) => {
+            for (i, item) in arr.iter().enumerate() {
+                index_anchors(item, &format!("{}/{}", pointer, i), index);
+            }
+        }
+        _ => {}
+    }
+}
+
+/// Checks the schema itself for defects: unresolved `$ref`s, `required`
+/// entries missing from `properties`, `default`/`examples` that violate
+/// their own subschema, invalid `pattern` regexes, and keywords `dialect`
+/// (or else the schema's own `$schema`) ignores.
+pub fn test_schema(schema: &Value, schema_dir: &Path, dialect: Option<Dialect>) -> Vec<String> {
+    let mut validator = Validator::new(schema, schema_dir);
+    if let Some(dialect) = dialect {
+        validator.dialect = dialect;
+    }
+    let mut defects = Vec::new();
+    check_schema_node(&mut validator, schema, "", &mut defects);
+    defects
+}
+
+/// The JSON Schema draft-07 meta-schema, for `meta_validate`.
+const META_SCHEMA: &str = include_str!("meta-schema/draft-07.json");
+
+/// Validates `schema` against the draft-07 meta-schema, checking that it's
+/// well-formed JSON Schema: keywords hold the right kinds of values, such
+/// as a list of names for `required` and a subschema for each property.
+/// Only the keywords the validator implements are enforced.
+pub fn meta_validate(schema: &Value) -> Vec<ValidationError> {
+    let meta: Value = serde_json::from_str(META_SCHEMA).expect("bundled meta-schema is JSON");
+    let mut validator = Validator::new(&meta, Path::new("."));
+    validator.validate_property("", schema, &meta);
+    validator.errors
+}
+
+fn check_schema_node(
+    validator: &mut Validator,
+    node: &Value,
+    pointer: &str,
+    defects: &mut Vec<String>,
+) {
+    let obj = match node {
+        Value::Object(obj) => obj,
+        Value::Array(arr) => {
+            for (i, item) in arr.iter().enumerate() {
+                check_schema_node(validator, item, &format!("{}/{}", pointer, i), defects);
+            }
+            return;
+        }
+        _ => return,
+    };
+    let location = if pointer.is_empty() { "/" } else { pointer };
+
+    if let Some(reference) = obj.get("$ref").and_then(|r| r.as_str()) {
+        if let Err(e) = validator.resolve_ref(reference) {
+            defects.push(format!("{}: {}", location, e));
+        }
+    }
+
+    if let Some(required) = obj.get("required").and_then(|r| r.as_array()) {
+        if let Some(properties) = obj.get("properties").and_then(|p| p.as_object()) {
+            for name in required.iter().filter_map(|r| r.as_str()) {
+                if !properties.contains_key(name) {
+                    defects.push(format!(
+                        "{}: required property \"{}\" is not defined in properties",
+                        location, name
+                    ));
+                }
+            }
+        }
+    }
+
+    if let Some(severity) = obj.get("x-severity") {
+        if severity != "error" && severity != "warning" {
+            defects.push(format!(
+                "{}: x-severity must be \"error\" or \"warning\", got {}",
+                location, severity
+            ));
+        }
+    }
+
+    if let Some(groups) = obj.get("x-oneOfFields") {
+        // Well-formed groups survive field_groups unchanged
+        let parsed = Value::from(field_groups(groups));
+        if parsed != *groups && parsed != Value::Array(vec![groups.clone()]) {
+            defects.push(format!(
+                "{}: x-oneOfFields must list field names, or groups of them, got {}",
+                location, groups
+            ));
+        }
+    }
+
+    if let Some(descriptions) = obj.get("x-enumDescriptions") {
+        match descriptions.as_object() {
+            Some(descriptions) => {
+                let members = obj.get("enum").and_then(|e| e.as_array());
+                for (key, description) in descriptions {
+                    if !description.is_string() {
+                        defects.push(format!(
+                            "{}: x-enumDescriptions[\"{}\"] must be a string, got {}",
+                            location, key, description
+                        ));
+                    }
+                    if !members.is_some_and(|m| m.iter().any(|member| enum_key(member) == *key)) {
+                        defects.push(format!(
+                            "{}: x-enumDescriptions describes \"{}\", which isn't in enum",
+                            location, key
+                        ));
+                    }
+                }
+            }
+            None => defects.push(format!(
+                "{}: x-enumDescriptions must be an object keyed by enum value, got {}",
+                location, descriptions
+            )),
+        }
+    }
+
+    if let Some(sorted_by) = obj.get("x-sortedBy") {
+        let field = sorted_by.get("field").is_some_and(|f| f.is_string());
+        let order = !sorted_by
+            .get("order")
+            .is_some_and(|o| o != "asc" && o != "desc");
+        if !sorted_by.is_object() || !field || !order {
+            defects.push(format!(
+                "{}: x-sortedBy must be {{\"field\": <name>, \"order\": \"asc\" or \"desc\"}}, got {}",
+                location, sorted_by
+            ));
+        }
+    }
+
+    if let Some(rules) = obj.get("x-requiredIf") {
+        let valid_rule = |rule: &Value| {
+            rule.get("field").is_some_and(|f| f.is_string())
+                && rule.get("equals").is_some()
+                && rule
+                    .get("required")
+                    .and_then(|r| r.as_array())
+                    .is_some_and(|r| r.iter().all(|f| f.is_string()))
+        };
+        let valid = match rules {
+            Value::Array(rules) => rules.iter().all(valid_rule),
+            rule => valid_rule(rule),
+        };
+        if !valid {
+            defects.push(format!(
+                "{}: x-requiredIf must be {{\"field\", \"equals\", \"required\": [names]}} or a list of them, got {}",
+                location, rules
+            ));
+        }
+    }
+
+    if let Some(replacement) = obj.get("x-deprecatedReplacement") {
+        if !replacement.is_string() {
+            defects.push(format!(
+                "{}: x-deprecatedReplacement must be a string, got {}",
+                location, replacement
+            ));
+        }
+    }
+
+    // Keywords from another draft are silently ignored when validating;
+    // a `dependencies` holding strings is more likely a property schema
+    let dialect = validator.dialect;
+    let mut ignored = Vec::new();
+    let prefix_items = obj.get("prefixItems").is_some_and(|p| p.is_array());
+    if dialect == Dialect::Draft2020 && prefix_items {
+        if obj.get("items").is_some_and(|i| i.is_array()) {
+            ignored.push((
+                "an array-valued items beside prefixItems",
+                "a single items schema",
+            ));
+        }
+        if obj.contains_key("additionalItems") {
+            ignored.push(("additionalItems beside prefixItems", "items"));
+        }
+    } else if prefix_items {
+        ignored.push(("prefixItems", "an array-valued items"));
+    }
+    if dialect == Dialect::Draft07 {
+        for keyword in ["dependentRequired", "dependentSchemas"] {
+            if obj.get(keyword).is_some_and(|d| d.is_object()) {
+                ignored.push((keyword, "dependencies"));
+            }
+        }
+    } else if obj
+        .get("dependencies")
+        .and_then(|d| d.as_object())
+        .is_some_and(|d| d.values().all(|v| !v.is_string()))
+    {
+        ignored.push(("dependencies", "dependentRequired or dependentSchemas"));
+    }
+    for (keyword, instead) in ignored {
+        defects.push(format!(
+            "{}: {} is ignored in {}, use {} instead",
+            location,
+            keyword,
+            dialect.name(),
+            instead
+        ));
+    }
+
+    for keyword in ["exclusiveMinimum", "exclusiveMaximum"] {
+        if let Some(bound) = obj.get(keyword).filter(|b| b.is_boolean()) {
+            defects.push(format!(
+                "{}: {} must be a number in draft-07 and later, got {} (the draft-04 form)",
+                location, keyword, bound
+            ));
+        }
+    }
+
+    if let Some(limit) = obj.get("x-maxDecodedBytes") {
+        if !limit.is_u64() {
+            defects.push(format!(
+                "{}: x-maxDecodedBytes must be a non-negative integer, got {}",
+                location, limit
+            ));
+        }
+    }
+
+    if let Some(description) = obj.get("x-patternDescription") {
+        if !description.is_string() {
+            defects.push(format!(
+                "{}: x-patternDescription must be a string, got {}",
+                location, description
+            ));
+        }
+    }
+
+    if let Some(examples) = obj.get("x-patternExamples") {
+        match examples.as_array() {
+            Some(examples) if examples.iter().all(|e| e.is_string()) => {
+                let pattern = obj.get("pattern").and_then(|p| p.as_str());
+                if let Some(re) = pattern.and_then(|p| Regex::new(p).ok()) {
+                    for example in examples.iter().filter_map(|e| e.as_str()) {
+                        if !re.is_match(example) {
+                            defects.push(format!(
+                                "{}: x-patternExamples entry \"{}\" doesn't match the pattern",
+                                location, example
+                            ));
+                        }
+                    }
+                }
+            }
+            _ => defects.push(format!(
+                "{}: x-patternExamples must be a list of strings, got {}",
+                location, examples
+            )),
+        }
+    }
+
+    if let Some(kind) = obj.get("x-localPath") {
+        if kind != "file" && kind != "image" {
+            defects.push(format!(
+                "{}: x-localPath must be \"file\" or \"image\", got {}",
+                location, kind
+            ));
+        }
+    }
+
+    if let Some(steps) = obj.get("x-normalize") {
+        let known = steps.as_array().is_some_and(|steps| {
+            steps
+                .iter()
+                .all(|step| step.as_str().is_some_and(|s| NORMALIZATIONS.contains(&s)))
+        });
+        if !known {
+            defects.push(format!(
+                "{}: x-normalize must be a list of {}, got {}",
+                location,
+                NORMALIZATIONS.join(", "),
+                steps
+            ));
+        }
+    }
+
+    if let Some(names) = obj.get("x-aliases") {
+        if !names
+            .as_array()
+            .is_some_and(|n| n.iter().all(|n| n.is_string()))
+        {
+            defects.push(format!(
+                "{}: x-aliases must be a list of old property names, got {}",
+                location, names
+            ));
+        }
+    }
+
+    // An alias that's also a property name would be validated twice
+    if let Some(properties) = obj.get("properties").and_then(|p| p.as_object()) {
+        for (name, property) in properties {
+            for alias in aliases(property).filter(|a| properties.contains_key(*a)) {
+                defects.push(format!(
+                    "{}: alias \"{}\" of \"{}\" is also a property",
+                    location, alias, name
+                ));
+            }
+        }
+    }
+
+    if let Some(pattern) = obj.get("pattern").and_then(|p| p.as_str()) {
+        if let Err(e) = Regex::new(pattern) {
+            // regex errors are multi-line diagrams; the last line is the reason
+            let message = e.to_string();
+            let reason = message.lines().last().unwrap_or_default();
+            let reason = reason.strip_prefix("error: ").unwrap_or(reason);
+            defects.push(format!(
+                "{}: invalid pattern \"{}\" ({})",
+                location, pattern, reason
+            ));
+        }
+    }
+
+    // default and examples must satisfy the subschema they annotate
+    let mut samples = Vec::new();
+    if let Some(default) = obj.get("default") {
+        samples.push((format!("{}/default", pointer), default));
+    }
+    if let Some(examples) = obj.get("examples").and_then(|e| e.as_array()) {
+        for (i, example) in examples.iter().enumerate() {
+            samples.push((format!("{}/examples/{}", pointer, i), example));
+        }
+    }
+    for (sample_pointer, sample) in samples {
+        validator.errors.clear();
+        validator.validate_property(&sample_pointer, sample, node);
+        defects.extend(validator.errors.drain(..).map(|e| e.to_string()));
+    }
+
+    for (key, child) in obj {
+        // These hold data values, not subschemas
+        if matches!(
+            key.as_str(),
+            "default" | "examples" | "const" | "enum" | "x-enumDescriptions"
+        ) {
+            continue;
+        }
+        let child_pointer = format!("{}/{}", pointer, key.replace('~', "~0").replace('/', "~1"));
+        match child {
+            Value::Object(map) if SCHEMA_MAPS.contains(&key.as_str()) => {
+                for (name, schema) in map {
+                    let name = name.replace('~', "~0").replace('/', "~1");
+                    check_schema_node(
+                        validator,
+                        schema,
+                        &format!("{}/{}", child_pointer, name),
+                        defects,
+                    );
+                }
+            }
+            _ => check_schema_node(validator, child, &child_pointer, defects),
+        }
+    }
+}
+
+/// The groups of field names in an `x-distinct`-style value, which holds
+/// one group (`["homepage", "repository"]`) or a list of groups. Names
+/// that aren't strings are dropped.
+fn field_groups(value: &Value) -> Vec<Vec<&str>> {
+    let Some(items) = value.as_array() else {
+        return Vec::new();
+    };
+    let groups: Vec<&Vec<Value>> = if items.iter().all(|g| g.is_string()) {
+        vec![items]
+    } else {
+        items.iter().filter_map(|g| g.as_array()).collect()
+    };
+    groups
+        .into_iter()
+        .map(|group| group.iter().filter_map(|f| f.as_str()).collect())
+        .collect()
+}
+
+/// Walks a JSON pointer through `data`, returning the equivalent data path
+/// (`/maintainers/0/1` becomes `.maintainers[0][1]`) and the value there.
+pub fn resolve_pointer<'v>(data: &'v Value, pointer: &str) -> Option<(String, &'v Value)> {
+    let mut path = String::new();
+    let mut current = data;
+    if pointer.is_empty() {
+        return Some((path, current));
+    }
+    for token in pointer.strip_prefix('/')?.split('/') {
+        let token = token.replace("~1", "/").replace("~0", "~");
+        match current {
+            Value::Object(obj) => {
+                current = obj.get(&token)?;
+                path.push_str(&format!(".{}", token));
+            }
+            Value::Array(arr) => {
+                let index: usize = token.parse().ok()?;
+                current = arr.get(index)?;
+                path.push_str(&format!("[{}]", index));
+            }
+            _ => return None,
+        }
+    }
+    Some((path, current))
+}
+
+/// The old property names a property's subschema lists in `x-aliases`.
+fn aliases(schema: &Value) -> impl Iterator<Item = &str> {
+    schema
+        .get("x-aliases")
+        .and_then(|a| a.as_array())
+        .into_iter()
+        .flatten()
+        .filter_map(|a| a.as_str())
+}
+
+/// Error label for a data path; the root object has the empty path.
+pub fn display_path(path: &str) -> &str {
+    if path.is_empty() {
+        "root"
+    } else {
+        path
+    }
+}
+
+/// Canonical form for equality checks: strings are trimmed, lowercased and
+/// lose any trailing `/`, so `https://x.dev/` matches `https://X.dev`. Git
+/// URLs compare by host and path, so `git@github.com:o/r.git` matches
+/// `https://github.com/o/r`.
+fn normalize_for_comparison(value: &Value) -> Value {
+    match value.as_str() {
+        Some(s) => {
+            let s = s.trim();
+            let canonical = normalize_git_url(s).unwrap_or_else(|| s.trim_end_matches('/').into());
+            Value::String(canonical.to_lowercase())
+        }
+        None => value.clone(),
+    }
+}
+
+fn is_valid_uri(s: &str) -> bool {
+    s.starts_with("http://") || s.starts_with("https://") || s.starts_with("data:image/")
+}
+
+/// Finds the name in `candidates` closest to `key`, if it's within a typo
+/// or two (one for short names).
+fn closest_name<'k>(key: &str, candidates: impl Iterator<Item = &'k str>) -> Option<&'k str> {
+    let key = key.to_lowercase();
+    let limit = if key.chars().count() <= 4 { 1 } else { 2 };
+    candidates
+        .map(|candidate| (edit_distance(&key, &candidate.to_lowercase()), candidate))
+        .filter(|&(distance, _)| distance <= limit)
+        .min()
+        .map(|(_, candidate)| candidate)
+}
+
+/// Whether `value` is a JSON Schema `integer` or `number`. Integers
+/// include floats without a fractional part, such as `1.0`.
+fn is_json_type(value: &Value, expected: &str) -> bool {
+    match expected {
+        "integer" => {
+            value.is_i64() || value.is_u64() || value.as_f64().is_some_and(|f| f.fract() == 0.0)
+        }
+        _ => value.is_number(),
+    }
+}
+
+/// Whether `value` has the JSON Schema type `name`; unknown names match
+/// anything.
+fn has_type(value: &Value, name: &str) -> bool {
+    match name {
+        "string" => value.is_string(),
+        "integer" | "number" => is_json_type(value, name),
+        "array" => value.is_array(),
+        "object" => value.is_object(),
+        "boolean" => value.is_boolean(),
+        "null" => value.is_null(),
+        _ => true,
+    }
+}
+
+/// Parses a string holding a number of type `expected`, for suggesting the
+/// unquoted value.
+fn coerce_number(s: &str, expected: &str) -> Option<Value> {
+    let s = s.trim();
+    if let Ok(i) = s.parse::<i64>() {
+        return Some(Value::from(i));
+    }
+    if expected == "number" {
+        let f = s.parse::<f64>().ok().filter(|f| f.is_finite())?;
+        return serde_json::Number::from_f64(f).map(Value::Number);
+    }
+    None
+}
+
+/// The `x-enumDescriptions` key for an enum member: a string as itself,
+/// anything else as its JSON text.
+fn enum_key(member: &Value) -> String {
+    match member {
+        Value::String(s) => s.clone(),
+        other => other.to_string(),
+    }
+}
+
+/// JSON Schema equality: objects compare regardless of key order, arrays
+/// element by element, and numbers by value, so `1` equals `1.0`.
+fn json_equal(a: &Value, b: &Value) -> bool {
+    match (a, b) {
+        (Value::Number(x), Value::Number(y)) => match (x.as_i64(), y.as_i64()) {
+            (Some(x), Some(y)) => x == y,
+            _ => x == y || ((x.is_f64() || y.is_f64()) && x.as_f64() == y.as_f64()),
+        },
+        (Value::Array(x), Value::Array(y)) => {
+            x.len() == y.len() && x.iter().zip(y).all(|(x, y)| json_equal(x, y))
+        }
+        (Value::Object(x), Value::Object(y)) => {
+            x.len() == y.len()
+                && x.iter()
+                    .all(|(key, x)| y.get(key).is_some_and(|y| json_equal(x, y)))
+        }
+        _ => a == b,
+    }
+}
+
+/// Levenshtein distance between `a` and `b`, counted in chars.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, cb) in b.iter().enumerate() {
+            let substitution = diagonal + usize::from(ca != *cb);
+            diagonal = row[j + 1];
+            row[j + 1] = substitution.min(row[j] + 1).min(diagonal + 1);
+        }
+    }
+    row[b.len()]
+}
+
+/// Suggests a fixed URI for a trimmable or scheme-less value such as
+/// `example.com`; `None` unless the result is a valid URI.
+fn suggest_uri(s: &str) -> Option<String> {
+    let trimmed = s.trim();
+    if is_valid_uri(trimmed) {
+        return Some(trimmed.to_string());
+    }
+    let looks_like_host = !trimmed.contains("://")
+        && !trimmed.contains(['@', ' '])
+        && trimmed.contains('.')
+        && trimmed.starts_with(|c: char| c.is_ascii_alphanumeric());
+    looks_like_host.then(|| format!("https://{}", trimmed))
+}
+
+/// Suggests a fixed email for surrounding whitespace, a `mailto:` prefix or
+/// a doubled `@`; `None` unless the result is a valid email.
+fn suggest_email(s: &str) -> Option<String> {
+    let trimmed = s.trim();
+    let trimmed = trimmed.strip_prefix("mailto:").unwrap_or(trimmed);
+    let fixed = Regex::new("@{2,}").unwrap().replace_all(trimmed, "@");
+    (fixed != s && is_valid_email(&fixed)).then(|| fixed.into_owned())
+}
+
+/// Returns the media subtype of a `data:image/...` URI, e.g. `svg+xml`.
+fn data_image_subtype(s: &str) -> Option<&str> {
+    let rest = s.strip_prefix("data:image/")?;
+    let end = rest.find([';', ',']).unwrap_or(rest.len());
+    Some(&rest[..end])
+}
+
+/// The size in bytes of a `data:` URI's payload once decoded: base64 when
+/// the URI declares `;base64`, percent-encoded text otherwise.
+fn data_uri_size(s: &str) -> Option<usize> {
+    let (header, payload) = s.strip_prefix("data:")?.split_once(',')?;
+    if header.ends_with(";base64") {
+        let digits = payload
+            .bytes()
+            .filter(|b| !b.is_ascii_whitespace() && *b != b'=')
+            .count();
+        // Every 4 digits hold 3 bytes; a partial group of n digits, n - 1
+        Some(digits / 4 * 3 + (digits % 4).saturating_sub(1))
+    } else {
+        Some(
+            payload
+                .len()
+                .saturating_sub(2 * payload.matches('%').count()),
+        )
+    }
+}
+
+/// Why `s` is too big, when it's a `data:` URI whose decoded payload is
+/// over `limit` bytes, e.g. `inline image is 412 KB, exceeds 100 KB limit`.
+pub fn oversized_data_uri(s: &str, limit: u64) -> Option<String> {
+    let size = data_uri_size(s)? as u64;
+    let kind = if s.starts_with("data:image/") {
+        "image"
+    } else {
+        "data"
+    };
+    (size > limit).then(|| {
+        format!(
+            "inline {} is {}, exceeds {} limit",
+            kind,
+            human_size(size),
+            human_size(limit)
+        )
+    })
+}
+
+/// `bytes` as `N bytes` below 1 KB, else in whole KB (1024 bytes).
+fn human_size(bytes: u64) -> String {
+    if bytes < 1024 {
+        format!("{} bytes", bytes)
+    } else {
+        format!("{:.0} KB", bytes as f64 / 1024.0)
+    }
+}
+
+/// Host and path prefix a funding platform's URLs start with, for the
+/// platform names GitHub's `FUNDING.yml` uses.
+fn funding_url_prefix(platform: &str) -> Option<&'static str> {
+    match platform {
+        "github" => Some("github.com/sponsors/"),
+        "open_collective" => Some("opencollective.com/"),
+        "patreon" => Some("patreon.com/"),
+        "ko_fi" => Some("ko-fi.com/"),
+        "liberapay" => Some("liberapay.com/"),
+        _ => None,
+    }
+}
+
+/// Whether `s` is an http(s) URL under `prefix` naming an account.
+fn is_funding_url(s: &str, prefix: &str) -> bool {
+    let Some(rest) = s
+        .strip_prefix("https://")
+        .or_else(|| s.strip_prefix("http://"))
+    else {
+        return false;
+    };
+    let rest = rest.strip_prefix("www.").unwrap_or(rest);
+    rest.strip_prefix(prefix)
+        .is_some_and(|account| !account.trim_end_matches('/').is_empty())
+}
+
+/// Plain `email` is ASCII-only; `idn-email` allows Unicode.
+fn is_valid_email(s: &str) -> bool {
+    let re = Regex::new(r"^[^\s@]+@[^\s@]+\.[^\s@]+$").unwrap();
+    s.is_ascii() && re.is_match(s)
+}
+
+/// An email whose domain may hold Unicode labels (`user@münchen.de`). Each
+/// label is letters, digits and inner hyphens, at most 63 characters.
+fn is_valid_idn_email(s: &str) -> bool {
+    let Some((local, domain)) = s.rsplit_once('@') else {
+        return false;
+    };
+    let labels: Vec<&str> = domain.split('.').collect();
+    !local.is_empty()
+        && !local.contains(|c: char| c.is_whitespace() || c == '@')
+        && labels.len() >= 2
+        && labels.iter().all(|label| {
+            !label.is_empty()
+                && label.chars().count() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_alphanumeric() || c == '-')
+        })
+}
+
+/// Parses an RFC 3339 full-date (`2024-02-29`) into (year, month, day),
+/// checking the day against the month's length.
+fn parse_date(s: &str) -> Option<(u32, u32, u32)> {
+    let bytes = s.as_bytes();
+    if bytes.len() != 10 || bytes[4] != b'-' || bytes[7] != b'-' {
+        return None;
+    }
+    let number = |range: std::ops::Range<usize>| -> Option<u32> {
+        let digits = &s[range];
+        digits
+            .bytes()
+            .all(|b| b.is_ascii_digit())
+            .then(|| digits.parse().ok())?
+    };
+    let (year, month, day) = (number(0..4)?, number(5..7)?, number(8..10)?);
+    let leap = year % 4 == 0 && (year % 100 != 0 || year % 400 == 0);
+    let days = match month {
+        1 | 3 | 5 | 7 | 8 | 10 | 12 => 31,
+        4 | 6 | 9 | 11 => 30,
+        2 if leap => 29,
+        2 => 28,
+        _ => return None,
+    };
+    (1..=days).contains(&day).then_some((year, month, day))
+}
+
+/// Orders two `x-sortedBy` keys, or `None` when they aren't comparable.
+fn compare_sort_keys(a: &Value, b: &Value) -> Option<Ordering> {
+    match (a, b) {
+        (Value::String(a), Value::String(b)) => match (parse_date(a), parse_date(b)) {
+            (Some(a), Some(b)) => Some(a.cmp(&b)),
+            _ => Some(a.cmp(b)),
+        },
+        (Value::Number(a), Value::Number(b)) => a.as_f64()?.partial_cmp(&b.as_f64()?),
+        _ => None,
+    }
+}
+
+/// Checks RFC 6570 URI template syntax: literals with `%XX` escapes, and
+/// `{...}` expressions of an optional operator and comma-separated
+/// variables, each with an optional `:N` prefix or `*` explode modifier.
+fn is_valid_uri_template(s: &str) -> bool {
+    let mut rest = s;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '{' => {
+                let Some(end) = rest.find('}') else {
+                    return false;
+                };
+                if !is_valid_template_expression(&rest[1..end]) {
+                    return false;
+                }
+                rest = &rest[end + 1..];
+            }
+            '%' => match skip_pct_encoded(rest) {
+                Some(after) => rest = after,
+                None => return false,
+            },
+            // Characters RFC 6570 excludes from literals
+            c if c.is_control() || " \"'<>\\^`|}".contains(c) => return false,
+            c => rest = &rest[c.len_utf8()..],
+        }
+    }
+    true
+}
+
+/// The inside of one `{...}` URI template expression.
+fn is_valid_template_expression(expression: &str) -> bool {
+    let variables = expression
+        .strip_prefix(['+', '#', '.', '/', ';', '?', '&'])
+        .unwrap_or(expression);
+    variables.split(',').all(|varspec| {
+        let (name, modifier) = match varspec.find([':', '*']) {
+            Some(i) => varspec.split_at(i),
+            None => (varspec, ""),
+        };
+        let modifier_ok = match modifier.strip_prefix(':') {
+            // A prefix length is 1 to 9999, without leading zeros
+            Some(length) => {
+                (1..=4).contains(&length.len())
+                    && !length.starts_with('0')
+                    && length.bytes().all(|b| b.is_ascii_digit())
+            }
+            None => modifier.is_empty() || modifier == "*",
+        };
+        modifier_ok && is_valid_template_varname(name)
+    })
+}
+
+/// `varname = varchar *( ["."] varchar )`, where a varchar is a letter,
+/// digit, `_` or `%XX` escape.
+fn is_valid_template_varname(name: &str) -> bool {
+    let mut rest = name;
+    let mut previous_dot = true;
+    while let Some(c) = rest.chars().next() {
+        match c {
+            '.' if !previous_dot => {
+                previous_dot = true;
+                rest = &rest[1..];
+                continue;
+            }
+            '%' => match skip_pct_encoded(rest) {
+                Some(after) => rest = after,
+                None => return false,
+            },
+            c if c.is_ascii_alphanumeric() || c == '_' => rest = &rest[1..],
+            _ => return false,
+        }
+        previous_dot = false;
+    }
+    !previous_dot
+}
+
+/// The text after a leading `%XX` escape, if `s` starts with one.
+fn skip_pct_encoded(s: &str) -> Option<&str> {
+    let hex = s.get(1..3)?;
+    hex.bytes().all(|b| b.is_ascii_hexdigit()).then(|| &s[3..])
+}
+
+/// E.164: `+`, then 1 to 15 digits starting with a country code (never
+/// 0), with no spaces or punctuation.
+fn is_valid_phone(s: &str) -> bool {
+    match s.strip_prefix('+') {
+        Some(digits) => {
+            (1..=15).contains(&digits.len())
+                && !digits.starts_with('0')
+                && digits.bytes().all(|b| b.is_ascii_digit())
+        }
+        None => false,
+    }
+}
+
+/// RFC 4648 base32: `A`-`Z` and `2`-`7`, optionally padded with `=` to a
+/// multiple of 8 characters. Unpadded, the length must be one a whole
+/// number of bytes encodes to.
+fn is_valid_base32(s: &str) -> bool {
+    let data = s.trim_end_matches('=');
+    let padding = s.len() - data.len();
+    // Characters past the last full group of 8
+    let partial = data.len() % 8;
+    data.bytes()
+        .all(|b| b.is_ascii_uppercase() || (b'2'..=b'7').contains(&b))
+        && matches!(partial, 0 | 2 | 4 | 5 | 7)
+        && (padding == 0 || (partial != 0 && padding == 8 - partial))
+}
+
+/// RFC 4648 base64url: `A`-`Z`, `a`-`z`, `0`-`9`, `-` and `_`, without
+/// `=` padding.
+fn is_valid_base64url(s: &str) -> bool {
+    s.bytes()
+        .all(|b| b.is_ascii_alphanumeric() || b == b'-' || b == b'_')
+        && s.len() % 4 != 1
+}
+
+/// Schemes git accepts for remotes, besides the SCP-like `user@host:path`.
+const GIT_URL_SCHEMES: &[&str] = &["https", "http", "git", "ssh", "git+ssh", "ssh+git"];
+
+/// A git remote URL (`https://`, `http://`, `git://`, `ssh://` or the
+/// SCP-like `git@host:owner/repo.git`) reduced to `host/path` for
+/// comparison: the host lowercased, without the scheme, user, port, a
+/// trailing `/` or `.git`. `None` when `s` isn't such a URL.
+fn normalize_git_url(s: &str) -> Option<String> {
+    let (authority, path) = match s.split_once("://") {
+        Some((scheme, rest)) => {
+            if !GIT_URL_SCHEMES.contains(&scheme.to_ascii_lowercase().as_str()) {
+                return None;
+            }
+            let rest = rest.split(['?', '#']).next().unwrap_or_default();
+            rest.split_once('/')?
+        }
+        // scp-like syntax needs a user, and a colon before any slash
+        None => {
+            let (authority, path) = s.split_once(':')?;
+            if !authority.contains('@') || authority.contains('/') {
+                return None;
+            }
+            (authority, path)
+        }
+    };
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = match host.rsplit_once(':') {
+        Some((host, port)) if port.bytes().all(|b| b.is_ascii_digit()) => host,
+        Some(_) => return None,
+        None => host,
+    };
+    let path = path.trim_start_matches('/').trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+    let valid_host = !host.is_empty()
+        && host
+            .bytes()
+            .all(|b| b.is_ascii_alphanumeric() || b == b'.' || b == b'-');
+    let valid_path = !path.is_empty() && !path.contains(char::is_whitespace);
+    (valid_host && valid_path).then(|| format!("{}/{}", host.to_ascii_lowercase(), path))
+}
+
+/// Accepts CSS hex colors: `#RGB`, `#RRGGBB` or `#RRGGBBAA`.
+fn is_valid_hex_color(s: &str) -> bool {
+    match s.strip_prefix('#') {
+        Some(hex) => matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()),
+        None => false,
+    }
+}
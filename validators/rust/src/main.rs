@@ -1,21 +1,1580 @@
-use json_comments::StripComments;
+use gitinfo_validator::{
+    display_path, meta_validate, oversized_data_uri, parse_jsonc_reader, resolve_pointer,
+    strip_jsonc, test_schema, Alias, Dialect, LocalFile, Normalization, Severity, ValidationError,
+    Validator, DEFAULT_MAX_DEPTH,
+};
 use regex::Regex;
 use serde_json::Value;
-use std::collections::HashSet;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
 use std::env;
 use std::fs;
-use std::io::Read;
-use std::path::Path;
+use std::io::{IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
 use std::process;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
 
-const RED: &str = "\x1b[0;31m";
-const GREEN: &str = "\x1b[0;32m";
-const NC: &str = "\x1b[0m";
+/// An ANSI escape sequence, written only while `COLOR` is on.
+struct Color(&'static str);
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if COLOR.load(Ordering::Relaxed) {
+            f.write_str(self.0)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Whether messages are colored, resolved from `--color` once the
+/// arguments are parsed.
+static COLOR: AtomicBool = AtomicBool::new(false);
+
+const RED: Color = Color("\x1b[0;31m");
+const GREEN: Color = Color("\x1b[0;32m");
+const YELLOW: Color = Color("\x1b[0;33m");
+const NC: Color = Color("\x1b[0m");
+
+/// Exit code: every file is valid.
+const EXIT_VALID: i32 = 0;
+/// Exit code: at least one file failed validation.
+const EXIT_INVALID: i32 = 1;
+/// Exit code: usage, IO or schema error.
+const EXIT_ERROR: i32 = 2;
+
+/// What each exit code means, for `--explain-exit-code` and the README's
+/// Exit Codes table.
+const EXIT_CODES: &[(i32, &str)] = &[
+    (EXIT_VALID, "Every file is valid"),
+    (
+        EXIT_INVALID,
+        "At least one file failed validation (including unparseable JSONC)",
+    ),
+    (
+        EXIT_ERROR,
+        "Usage, IO or schema error (unknown option, file not found, bad schema)",
+    ),
+];
+
+/// What the invocation should do.
+#[derive(PartialEq)]
+enum Command {
+    /// Validate each file (the default).
+    Validate,
+    /// Compare two files field by field.
+    Diff,
+    /// Summarize fields across many files.
+    Stats,
+    /// Print a shell completion script (not advertised in the usage).
+    Completions,
+}
+
+/// Fields `stats` tallies when `--fields` isn't given.
+const DEFAULT_STATS_FIELDS: &[&str] = &["license", "description", "homepage", "tags"];
+/// Most common values `stats` lists per field.
+const STATS_TOP_VALUES: usize = 10;
+
+/// Strings `--warn-placeholders` flags unless `--placeholders` is given.
+const DEFAULT_PLACEHOLDERS: &[&str] = &[
+    "TODO",
+    "FIXME",
+    "TBD",
+    "changeme",
+    "Your description here",
+    "My awesome project",
+    "Lorem ipsum",
+];
+
+/// How input files are parsed.
+#[derive(Clone, Copy, PartialEq)]
+enum InputFormat {
+    /// Strict JSON.
+    Json,
+    /// JSON with comments and trailing commas (the default).
+    Jsonc,
+    Toml,
+    Yaml,
+}
+
+/// How results are reported.
+#[derive(Clone, Copy, PartialEq)]
+enum Format {
+    /// Colored messages on the terminal (the default).
+    Text,
+    /// A self-contained HTML page, written once every file is checked.
+    Html,
+    /// A Code Climate issue array, for GitLab's code quality reports.
+    Codeclimate,
+    /// JUnit XML with a test case per file, for CI test reports.
+    Junit,
+    /// `path:line: message` lines that GitLab's job log links to the file.
+    GitlabAnnotations,
+    /// One JSON object per error or warning, written as each file is checked.
+    Ndjson,
+}
+
+/// When messages are colored.
+#[derive(Clone, Copy, PartialEq)]
+enum ColorChoice {
+    Always,
+    /// Only on a terminal, with `NO_COLOR` unset, for text reports not
+    /// written to `--output` (the default).
+    Auto,
+    Never,
+}
+
+/// Command-line options.
+struct Options {
+    command: Command,
+    /// Paths to `.gitinfo` files; `-` reads from stdin.
+    files: Vec<String>,
+    /// Name to report for stdin input in diagnostics.
+    stdin_filename: Option<String>,
+    /// Don't warn when a file isn't named `.gitinfo`.
+    any_name: bool,
+    /// Stop collecting errors for a file after this many.
+    max_errors: Option<usize>,
+    /// Print the fields checked, with schema annotations.
+    verbose: bool,
+    /// Don't print anything for valid files.
+    quiet: bool,
+    /// Warn when a `readOnly` field appears in the file.
+    no_edit_readonly: bool,
+    /// Schemas to validate against instead of the discovered one; any after
+    /// the first are overlays applied on top of it.
+    schemas: Vec<String>,
+    /// Directory of `gitinfo.v<N>.schema.json` files picked by `schemaVersion`.
+    schema_dir: Option<String>,
+    /// Overlay schemas deep-merged into the base, in order.
+    merge_schemas: Vec<String>,
+    /// Arrays in `--merge-schema` overlays replace the base's instead of
+    /// being appended to them.
+    replace_arrays: bool,
+    /// Print the merged base schema instead of validating.
+    print_merged_schema: bool,
+    /// Check the schema itself instead of validating files.
+    test_schema: bool,
+    /// Check the schema against the JSON Schema meta-schema instead of
+    /// validating.
+    validate_schema_only: bool,
+    /// Print the schema as Markdown documentation instead of validating.
+    explain_schema: bool,
+    /// Write a commented template for the schema instead of validating.
+    init: bool,
+    /// Replace typographic quotes with ASCII ones before parsing.
+    normalize_quotes: bool,
+    /// Parse JSON input as it's read, without holding the text in memory.
+    stream: bool,
+    /// Validate against the file's own `$schema` instead of the default.
+    use_declared_schema: bool,
+    /// Permit network access, e.g. to fetch a declared `$schema` URL.
+    allow_network: bool,
+    /// Directory caching fetched URL schemas between runs.
+    schema_cache: Option<String>,
+    /// Use only cached URL schemas, never the network.
+    offline: bool,
+    /// JSON pointer whose errors should be explained in detail.
+    explain_error: Option<String>,
+    /// Sort object keys and report errors in data-path order.
+    sort_keys: bool,
+    /// Validate each element of a top-level array as its own document.
+    each: bool,
+    /// Run schema-driven cross-field checks such as `x-distinct` and
+    /// `x-disjoint`.
+    check_cross_fields: bool,
+    /// Baseline of known errors to suppress.
+    baseline: Option<String>,
+    /// Where to record the current errors as a new baseline.
+    write_baseline: Option<String>,
+    /// Print only the total error count.
+    count_only: bool,
+    /// Rewrite files to apply suggested fixes.
+    fix: bool,
+    /// Rewrite `x-normalize` fields in their canonical form.
+    normalize: bool,
+    /// Treat warnings, including `x-severity` ones, as failures.
+    strict: bool,
+    /// Print per-phase timings to stderr.
+    profile: bool,
+    /// Interactively rename or delete unknown properties.
+    repair: bool,
+    /// Only validate `.gitinfo` files changed since this git revision.
+    since: Option<String>,
+    /// Fields (data paths without the leading `.`) for `stats` to tally.
+    fields: Vec<String>,
+    /// Top-level fields required on top of the schema's `required`.
+    require_fields: Vec<String>,
+    /// Require a comment above each top-level property of JSONC input.
+    require_comments: bool,
+    /// Report how many of the schema's optional properties are set.
+    coverage: bool,
+    /// Hosts, optionally followed by a path prefix, that `root` must be on.
+    repo_hosts: Vec<String>,
+    /// Largest decoded size of an inline `data:` icon.
+    max_logo_bytes: Option<u64>,
+    /// Rewrite valid files with canonical indentation.
+    fix_format: bool,
+    /// Spaces per level for `--fix-format`.
+    indent: usize,
+    /// JSON pointer of the only subtree to validate.
+    only: Option<String>,
+    /// Data path prefix of the errors to print; the rest still count.
+    errors_only_for: Option<String>,
+    input_format: InputFormat,
+    /// Stop at the first error, skipping the remaining checks and files.
+    fail_fast: bool,
+    /// Nesting limit for the validator's recursion.
+    max_depth: usize,
+    /// Draft semantics to validate with, overriding the schema's `$schema`.
+    dialect: Option<Dialect>,
+    /// Check that `x-localPath` fields name existing files.
+    check_files: bool,
+    /// Check declared fields against the repository's contents.
+    check_git: bool,
+    /// Check `dependencies` against the `name`s of the files validated.
+    workspace: bool,
+    /// `owner/name` of a GitHub repository to compare fields with.
+    github_repo: Option<String>,
+    /// `--language-extensions` entries, overriding `LANGUAGE_EXTENSIONS`.
+    language_extensions: Vec<(String, Vec<String>)>,
+    format: Format,
+    color: ColorChoice,
+    /// Prefix each error with the schema keyword that produced it.
+    trace: bool,
+    /// Warn about required string fields that are empty or blank.
+    warn_empty_strings: bool,
+    /// Warn about strings equal to a placeholder.
+    warn_placeholders: bool,
+    /// `--placeholders`, replacing `DEFAULT_PLACEHOLDERS`.
+    placeholders: Option<Vec<String>>,
+    /// File to write a `--format` report to instead of stdout.
+    output: Option<String>,
+    /// Directory to write a JUnit report per file to.
+    split_output: Option<String>,
+}
+
+fn parse_args(mut args: impl Iterator<Item = String>) -> Options {
+    let mut options = Options {
+        command: Command::Validate,
+        files: Vec::new(),
+        stdin_filename: None,
+        any_name: false,
+        max_errors: None,
+        verbose: false,
+        quiet: false,
+        no_edit_readonly: false,
+        schemas: Vec::new(),
+        schema_dir: None,
+        merge_schemas: Vec::new(),
+        replace_arrays: false,
+        print_merged_schema: false,
+        test_schema: false,
+        validate_schema_only: false,
+        explain_schema: false,
+        init: false,
+        normalize_quotes: false,
+        stream: false,
+        use_declared_schema: false,
+        allow_network: false,
+        schema_cache: None,
+        offline: false,
+        explain_error: None,
+        sort_keys: false,
+        each: false,
+        baseline: None,
+        write_baseline: None,
+        count_only: false,
+        fix: false,
+        normalize: false,
+        strict: false,
+        profile: false,
+        repair: false,
+        since: None,
+        input_format: InputFormat::Jsonc,
+        fields: Vec::new(),
+        require_fields: Vec::new(),
+        require_comments: false,
+        coverage: false,
+        repo_hosts: Vec::new(),
+        max_logo_bytes: None,
+        fix_format: false,
+        indent: 2,
+        only: None,
+        errors_only_for: None,
+        fail_fast: false,
+        max_depth: DEFAULT_MAX_DEPTH,
+        dialect: None,
+        check_files: false,
+        check_git: false,
+        workspace: false,
+        github_repo: None,
+        language_extensions: Vec::new(),
+        format: Format::Text,
+        color: ColorChoice::Auto,
+        trace: false,
+        warn_empty_strings: false,
+        warn_placeholders: false,
+        placeholders: None,
+        output: None,
+        split_output: None,
+        check_cross_fields: false,
+    };
+    let mut format_given = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--stdin-filename" => {
+                options.stdin_filename = Some(flag_value(&mut args, "--stdin-filename"))
+            }
+            "--any-name" => options.any_name = true,
+            "--help" | "-h" => {
+                print_help();
+                process::exit(EXIT_VALID);
+            }
+            "--explain-exit-code" => {
+                let value = flag_value(&mut args, "--explain-exit-code");
+                let meaning = EXIT_CODES
+                    .iter()
+                    .find(|(code, _)| value.trim().parse() == Ok(*code))
+                    .map(|(_, meaning)| meaning);
+                match meaning {
+                    Some(meaning) => {
+                        println!("{}: {}", value.trim(), meaning);
+                        println!("When several files are given, the most severe outcome wins.");
+                        process::exit(EXIT_VALID);
+                    }
+                    None => usage_error(&format!(
+                        "--explain-exit-code expects 0, 1 or 2, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "--quiet" | "-q" => options.quiet = true,
+            "--verbose" | "-v" => options.verbose = true,
+            "--no-edit-readonly" => options.no_edit_readonly = true,
+            "--schema" => options.schemas.push(flag_value(&mut args, "--schema")),
+            "--schema-dir" => options.schema_dir = Some(flag_value(&mut args, "--schema-dir")),
+            "--merge-schema" => options
+                .merge_schemas
+                .push(flag_value(&mut args, "--merge-schema")),
+            "--merge-arrays" => {
+                let value = flag_value(&mut args, "--merge-arrays");
+                options.replace_arrays = match value.as_str() {
+                    "append" => false,
+                    "replace" => true,
+                    _ => usage_error(&format!(
+                        "--merge-arrays expects append or replace, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "--print-merged-schema" => options.print_merged_schema = true,
+            "--test-schema" => options.test_schema = true,
+            "--validate-schema-only" => options.validate_schema_only = true,
+            "--explain-schema" => options.explain_schema = true,
+            "--init" => options.init = true,
+            "--normalize-quotes" => options.normalize_quotes = true,
+            "--stream" => options.stream = true,
+            "--use-declared-schema" => options.use_declared_schema = true,
+            "--allow-network" => options.allow_network = true,
+            "--schema-from-url-cache" => {
+                options.schema_cache = Some(flag_value(&mut args, "--schema-from-url-cache"))
+            }
+            "--offline" => options.offline = true,
+            "--sort-keys" => options.sort_keys = true,
+            "--each" => options.each = true,
+            "--explain-error" => {
+                options.explain_error = Some(flag_value(&mut args, "--explain-error"))
+            }
+            "--check-cross-fields" => options.check_cross_fields = true,
+            "--count-only" => options.count_only = true,
+            "--fix" => options.fix = true,
+            "--normalize" => options.normalize = true,
+            "--fix-format" => options.fix_format = true,
+            "--only" => options.only = Some(flag_value(&mut args, "--only")),
+            "--print-errors-only-for" => {
+                options.errors_only_for = Some(flag_value(&mut args, "--print-errors-only-for"))
+            }
+            "--indent" => {
+                let value = flag_value(&mut args, "--indent");
+                match value.parse::<usize>() {
+                    Ok(n) if n <= 16 => options.indent = n,
+                    _ => usage_error(&format!(
+                        "--indent expects a number of spaces from 0 to 16, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "--strict" => options.strict = true,
+            "--profile" => options.profile = true,
+            "--repair" => options.repair = true,
+            "--no-comments" => options.input_format = InputFormat::Json,
+            "--input-format" => {
+                let value = flag_value(&mut args, "--input-format");
+                options.input_format = match value.as_str() {
+                    "json" => InputFormat::Json,
+                    "jsonc" => InputFormat::Jsonc,
+                    "toml" => InputFormat::Toml,
+                    "yaml" => InputFormat::Yaml,
+                    _ => usage_error(&format!(
+                        "--input-format expects json, jsonc, toml or yaml, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "--fail-fast" => options.fail_fast = true,
+            "--check-files" => options.check_files = true,
+            "--check-git" => options.check_git = true,
+            "--workspace" => options.workspace = true,
+            "--github-repo" => {
+                let value = flag_value(&mut args, "--github-repo");
+                let valid = value.split_once('/').is_some_and(|(owner, name)| {
+                    !owner.is_empty() && !name.is_empty() && !name.contains('/')
+                });
+                if !valid {
+                    usage_error(&format!(
+                        "--github-repo expects owner/name, got \"{}\"",
+                        value
+                    ));
+                }
+                options.github_repo = Some(value);
+            }
+            "--language-extensions" => {
+                let value = flag_value(&mut args, "--language-extensions");
+                let Some((language, extensions)) = value.split_once('=') else {
+                    usage_error(&format!(
+                        "--language-extensions expects LANGUAGE=EXT[,EXT...], got \"{}\"",
+                        value
+                    ))
+                };
+                let extensions = extensions
+                    .split(',')
+                    .map(|e| e.trim().trim_start_matches('.').to_string())
+                    .filter(|e| !e.is_empty())
+                    .collect();
+                options
+                    .language_extensions
+                    .push((language.trim().to_string(), extensions));
+            }
+            "--trace" => options.trace = true,
+            "--warn-empty-strings" => options.warn_empty_strings = true,
+            "--warn-placeholders" => options.warn_placeholders = true,
+            "--placeholders" => {
+                let value = flag_value(&mut args, "--placeholders");
+                options.placeholders = Some(
+                    value
+                        .split(',')
+                        .map(|p| p.trim().to_string())
+                        .filter(|p| !p.is_empty())
+                        .collect(),
+                );
+            }
+            "--format" => {
+                let value = flag_value(&mut args, "--format");
+                options.format = match value.as_str() {
+                    "text" => Format::Text,
+                    "html" => Format::Html,
+                    "codeclimate" => Format::Codeclimate,
+                    "junit" => Format::Junit,
+                    "annotations-gitlab" => Format::GitlabAnnotations,
+                    "ndjson" => Format::Ndjson,
+                    _ => usage_error(&format!(
+                        "--format expects text, html, codeclimate, junit, annotations-gitlab or ndjson, got \"{}\"",
+                        value
+                    )),
+                };
+                format_given = true;
+            }
+            "--color" => {
+                let value = flag_value(&mut args, "--color");
+                options.color = match value.as_str() {
+                    "always" => ColorChoice::Always,
+                    "auto" => ColorChoice::Auto,
+                    "never" => ColorChoice::Never,
+                    _ => usage_error(&format!(
+                        "--color expects always, auto or never, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "--output" => options.output = Some(flag_value(&mut args, "--output")),
+            "--split-output" => {
+                options.split_output = Some(flag_value(&mut args, "--split-output"))
+            }
+            "--fields" => {
+                let value = flag_value(&mut args, "--fields");
+                options.fields = value
+                    .split(',')
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+                    .collect();
+            }
+            "--require-fields" => {
+                let value = flag_value(&mut args, "--require-fields");
+                options.require_fields = value
+                    .split(',')
+                    .map(|f| f.trim().to_string())
+                    .filter(|f| !f.is_empty())
+                    .collect();
+            }
+            "--require-comments" => options.require_comments = true,
+            "--coverage" => options.coverage = true,
+            "--repo-host-allowlist" => {
+                let value = flag_value(&mut args, "--repo-host-allowlist");
+                options.repo_hosts = value
+                    .split(',')
+                    .map(|h| h.trim().trim_end_matches('/').to_string())
+                    .filter(|h| !h.is_empty())
+                    .collect();
+            }
+            "--since" => options.since = Some(flag_value(&mut args, "--since")),
+            "--baseline" => options.baseline = Some(flag_value(&mut args, "--baseline")),
+            "--write-baseline" => {
+                options.write_baseline = Some(flag_value(&mut args, "--write-baseline"))
+            }
+            "--max-depth" => {
+                let value = flag_value(&mut args, "--max-depth");
+                match value.parse::<usize>() {
+                    Ok(n) if n > 0 => options.max_depth = n,
+                    _ => usage_error(&format!(
+                        "--max-depth expects a positive integer, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "--json-schema-dialect" => {
+                let value = flag_value(&mut args, "--json-schema-dialect");
+                match Dialect::from_name(&value) {
+                    Some(dialect) => options.dialect = Some(dialect),
+                    None => usage_error(&format!(
+                        "--json-schema-dialect expects draft-07, 2019-09 or 2020-12, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "--max-logo-bytes" => {
+                let value = flag_value(&mut args, "--max-logo-bytes");
+                match value.parse::<u64>() {
+                    Ok(n) => options.max_logo_bytes = Some(n),
+                    _ => usage_error(&format!(
+                        "--max-logo-bytes expects a number of bytes, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "--max-errors" => {
+                let value = flag_value(&mut args, "--max-errors");
+                match value.parse::<usize>() {
+                    Ok(n) if n > 0 => options.max_errors = Some(n),
+                    _ => usage_error(&format!(
+                        "--max-errors expects a positive integer, got \"{}\"",
+                        value
+                    )),
+                }
+            }
+            "-" => options.files.push(arg),
+            _ if arg.starts_with("--") => {
+                usage_error(&format!("Unknown option: {} (see --help)", arg))
+            }
+            "diff" if options.command == Command::Validate && options.files.is_empty() => {
+                options.command = Command::Diff
+            }
+            "stats" if options.command == Command::Validate && options.files.is_empty() => {
+                options.command = Command::Stats
+            }
+            "completions" if options.command == Command::Validate && options.files.is_empty() => {
+                options.command = Command::Completions
+            }
+            _ => options.files.push(arg),
+        }
+    }
+
+    if options.command == Command::Completions
+        && !matches!(options.files.as_slice(), [shell] if SHELLS.contains(&shell.as_str()))
+    {
+        usage_error("completions expects one shell: bash, zsh or fish");
+    }
+    if options.command == Command::Diff && options.files.len() != 2 {
+        usage_error("diff expects exactly two files");
+    }
+    if options.command == Command::Diff && options.count_only {
+        usage_error("--count-only can't be used with diff");
+    }
+    if options.command == Command::Diff && options.since.is_some() {
+        usage_error("--since can't be used with diff");
+    }
+    if options.command == Command::Stats && (options.count_only || options.since.is_some()) {
+        usage_error("--count-only and --since can't be used with stats");
+    }
+    if !options.fields.is_empty() && options.command != Command::Stats {
+        usage_error("--fields only applies to stats");
+    }
+    // GitLab sets GITLAB_CI in every job; its log viewer links annotations
+    if !format_given
+        && env::var_os("GITLAB_CI").is_some()
+        && options.command == Command::Validate
+        && !options.count_only
+        && options.output.is_none()
+    {
+        options.format = Format::GitlabAnnotations;
+    }
+    if options.format != Format::Text
+        && (options.command != Command::Validate || options.count_only)
+    {
+        usage_error("--format only applies when validating files, without --count-only");
+    }
+    let json_input = matches!(options.input_format, InputFormat::Json | InputFormat::Jsonc);
+    if (options.fix || options.repair || options.fix_format || options.normalize) && !json_input {
+        usage_error(
+            "--fix, --fix-format, --normalize and --repair only rewrite JSON and JSONC files",
+        );
+    }
+    if options.normalize && options.each {
+        usage_error("--normalize can't be used with --each");
+    }
+    if options.coverage && (options.format != Format::Text || options.count_only || options.each) {
+        usage_error("--coverage only applies to text output, without --count-only or --each");
+    }
+    if options.stream && matches!(options.input_format, InputFormat::Toml | InputFormat::Yaml) {
+        usage_error("--stream only reads JSON and JSONC files");
+    }
+    if options.stream && options.normalize_quotes {
+        usage_error("--stream can't be used with --normalize-quotes");
+    }
+    if options.require_comments && (options.input_format != InputFormat::Jsonc || options.stream) {
+        usage_error("--require-comments only checks JSONC files, without --stream");
+    }
+    if !options.merge_schemas.is_empty() && options.schema_dir.is_some() {
+        usage_error("--merge-schema can't be used with --schema-dir");
+    }
+    if options.placeholders.is_some() && !options.warn_placeholders {
+        usage_error("--placeholders needs --warn-placeholders");
+    }
+    if options.print_merged_schema && options.merge_schemas.is_empty() {
+        usage_error("--print-merged-schema needs at least one --merge-schema");
+    }
+    if options.offline && options.schema_cache.is_none() {
+        usage_error("--offline needs --schema-from-url-cache");
+    }
+    if options.offline && options.github_repo.is_some() {
+        usage_error("--github-repo can't be used with --offline");
+    }
+    if options.github_repo.is_some() && !options.allow_network {
+        usage_error("--github-repo needs --allow-network to fetch the repository");
+    }
+    if options.output.is_some() && options.format == Format::Text {
+        usage_error("--output needs a --format other than text");
+    }
+    if options.split_output.is_some() && options.format != Format::Junit {
+        usage_error("--split-output needs --format junit");
+    }
+    if options.split_output.is_some() && options.output.is_some() {
+        usage_error("--split-output and --output can't be used together");
+    }
+    if options.output.is_some() && options.format == Format::Ndjson {
+        usage_error("--format ndjson streams to stdout and can't be used with --output");
+    }
+
+    // --since picks its own files when none are given, stats the
+    // current directory
+    if options.files.is_empty() && (options.command == Command::Stats || options.workspace) {
+        options.files.push(".".to_string());
+    } else if options.files.is_empty() && options.since.is_none() {
+        options.files.push(".gitinfo".to_string());
+    }
+
+    options
+}
+
+/// What follows a flag on the command line, for `--help` and completions.
+#[derive(Clone, Copy)]
+enum FlagArg {
+    /// A switch that takes no value.
+    None,
+    File,
+    Dir,
+    /// One of a fixed set of words.
+    Choice(&'static [&'static str]),
+    /// Free text that can't be completed, shown as `<name>`.
+    Text(&'static str),
+}
+
+/// Every flag `parse_args` accepts: long name, short alias, value and a
+/// one-line summary, in the order `--help` lists them. The README's Options
+/// table has the details.
+const FLAGS: &[(&str, Option<&str>, FlagArg, &str)] = &[
+    (
+        "--help",
+        Some("-h"),
+        FlagArg::None,
+        "Print this help and exit",
+    ),
+    (
+        "--explain-exit-code",
+        None,
+        FlagArg::Text("n"),
+        "Print what exit code n means and exit",
+    ),
+    (
+        "--quiet",
+        Some("-q"),
+        FlagArg::None,
+        "Don't print a line for each valid file",
+    ),
+    (
+        "--verbose",
+        Some("-v"),
+        FlagArg::None,
+        "List the fields checked",
+    ),
+    (
+        "--any-name",
+        None,
+        FlagArg::None,
+        "Don't warn when a file isn't named .gitinfo",
+    ),
+    (
+        "--stdin-filename",
+        None,
+        FlagArg::Text("path"),
+        "Name to report for - (stdin)",
+    ),
+    (
+        "--schema",
+        None,
+        FlagArg::File,
+        "Validate against this schema; repeat for overlays",
+    ),
+    (
+        "--merge-schema",
+        None,
+        FlagArg::File,
+        "Deep-merge an overlay into the schema",
+    ),
+    (
+        "--merge-arrays",
+        None,
+        FlagArg::Choice(&["append", "replace"]),
+        "How --merge-schema combines arrays (default append)",
+    ),
+    (
+        "--print-merged-schema",
+        None,
+        FlagArg::None,
+        "Print the merged schema and exit",
+    ),
+    (
+        "--schema-dir",
+        None,
+        FlagArg::Dir,
+        "Pick gitinfo.v<N>.schema.json by schemaVersion",
+    ),
+    (
+        "--json-schema-dialect",
+        None,
+        FlagArg::Choice(&["draft-07", "2019-09", "2020-12"]),
+        "Draft semantics for items and dependencies",
+    ),
+    (
+        "--use-declared-schema",
+        None,
+        FlagArg::None,
+        "Validate against the file's own $schema",
+    ),
+    (
+        "--allow-network",
+        None,
+        FlagArg::None,
+        "Allow fetching URL schemas",
+    ),
+    (
+        "--schema-from-url-cache",
+        None,
+        FlagArg::Dir,
+        "Cache fetched URL schemas in a directory",
+    ),
+    (
+        "--offline",
+        None,
+        FlagArg::None,
+        "Use only cached URL schemas",
+    ),
+    (
+        "--test-schema",
+        None,
+        FlagArg::None,
+        "Self-test the schema instead of validating",
+    ),
+    (
+        "--validate-schema-only",
+        None,
+        FlagArg::None,
+        "Check the schema against the draft-07 meta-schema",
+    ),
+    (
+        "--init",
+        None,
+        FlagArg::None,
+        "Write a commented template for the schema",
+    ),
+    (
+        "--explain-schema",
+        None,
+        FlagArg::None,
+        "Print the schema as Markdown docs",
+    ),
+    (
+        "--input-format",
+        None,
+        FlagArg::Choice(&["json", "jsonc", "toml", "yaml"]),
+        "How to parse input files (default jsonc)",
+    ),
+    (
+        "--no-comments",
+        None,
+        FlagArg::None,
+        "Same as --input-format json",
+    ),
+    (
+        "--normalize-quotes",
+        None,
+        FlagArg::None,
+        "Replace typographic quotes before parsing",
+    ),
+    (
+        "--stream",
+        None,
+        FlagArg::None,
+        "Parse JSON as it's read, for very large files",
+    ),
+    (
+        "--no-edit-readonly",
+        None,
+        FlagArg::None,
+        "Warn about readOnly fields that are set",
+    ),
+    (
+        "--check-cross-fields",
+        None,
+        FlagArg::None,
+        "Run the schema's cross-field checks",
+    ),
+    (
+        "--check-files",
+        None,
+        FlagArg::None,
+        "Check x-localPath files on disk",
+    ),
+    (
+        "--check-git",
+        None,
+        FlagArg::None,
+        "Check declared fields against the repository",
+    ),
+    (
+        "--github-repo",
+        None,
+        FlagArg::Text("owner/name"),
+        "Compare fields with a GitHub repository",
+    ),
+    (
+        "--language-extensions",
+        None,
+        FlagArg::Text("language=ext,..."),
+        "Extensions --check-git looks for",
+    ),
+    (
+        "--workspace",
+        None,
+        FlagArg::None,
+        "Check dependencies against the validated files' names",
+    ),
+    (
+        "--warn-empty-strings",
+        None,
+        FlagArg::None,
+        "Warn about blank required strings",
+    ),
+    (
+        "--warn-placeholders",
+        None,
+        FlagArg::None,
+        "Warn about placeholder strings such as TODO",
+    ),
+    (
+        "--placeholders",
+        None,
+        FlagArg::Text("a,b,..."),
+        "Placeholders --warn-placeholders looks for",
+    ),
+    (
+        "--require-fields",
+        None,
+        FlagArg::Text("a,b,..."),
+        "Require top-level fields beyond the schema",
+    ),
+    (
+        "--require-comments",
+        None,
+        FlagArg::None,
+        "Require a // comment above each top-level property",
+    ),
+    (
+        "--repo-host-allowlist",
+        None,
+        FlagArg::Text("host/path,..."),
+        "Require root to be on one of these hosts",
+    ),
+    ("--strict", None, FlagArg::None, "Fail on warnings too"),
+    (
+        "--max-logo-bytes",
+        None,
+        FlagArg::Text("n"),
+        "Limit the decoded size of an inline data: icon",
+    ),
+    (
+        "--only",
+        None,
+        FlagArg::Text("pointer"),
+        "Validate only the subtree at a JSON pointer",
+    ),
+    (
+        "--each",
+        None,
+        FlagArg::None,
+        "Validate each element of a JSON array",
+    ),
+    (
+        "--since",
+        None,
+        FlagArg::Text("rev"),
+        "Validate .gitinfo files changed since a revision",
+    ),
+    (
+        "--fail-fast",
+        None,
+        FlagArg::None,
+        "Stop at the first error",
+    ),
+    (
+        "--max-depth",
+        None,
+        FlagArg::Text("n"),
+        "Nesting limit for validation (default 64)",
+    ),
+    (
+        "--max-errors",
+        None,
+        FlagArg::Text("n"),
+        "Print at most n errors per file",
+    ),
+    (
+        "--print-errors-only-for",
+        None,
+        FlagArg::Text("path"),
+        "Print only errors under a data path",
+    ),
+    (
+        "--sort-keys",
+        None,
+        FlagArg::None,
+        "Sort keys and list errors by path",
+    ),
+    (
+        "--trace",
+        None,
+        FlagArg::None,
+        "Prefix errors with their schema keyword",
+    ),
+    (
+        "--count-only",
+        None,
+        FlagArg::None,
+        "Print only the total error count",
+    ),
+    (
+        "--coverage",
+        None,
+        FlagArg::None,
+        "Report how many optional fields are set",
+    ),
+    (
+        "--explain-error",
+        None,
+        FlagArg::Text("pointer"),
+        "Explain the errors at a JSON pointer",
+    ),
+    (
+        "--baseline",
+        None,
+        FlagArg::File,
+        "Suppress errors recorded in a baseline",
+    ),
+    (
+        "--write-baseline",
+        None,
+        FlagArg::File,
+        "Record the current errors as a baseline",
+    ),
+    (
+        "--fix",
+        None,
+        FlagArg::None,
+        "Apply suggested fixes in place",
+    ),
+    (
+        "--normalize",
+        None,
+        FlagArg::None,
+        "Rewrite x-normalize fields in canonical form",
+    ),
+    (
+        "--repair",
+        None,
+        FlagArg::None,
+        "Interactively rename or delete unknown properties",
+    ),
+    ("--fix-format", None, FlagArg::None, "Reformat valid files"),
+    (
+        "--indent",
+        None,
+        FlagArg::Text("n"),
+        "Spaces per level for --fix-format (default 2)",
+    ),
+    (
+        "--format",
+        None,
+        FlagArg::Choice(&[
+            "text",
+            "html",
+            "codeclimate",
+            "junit",
+            "annotations-gitlab",
+            "ndjson",
+        ]),
+        "Report format (default text)",
+    ),
+    (
+        "--color",
+        None,
+        FlagArg::Choice(&["always", "auto", "never"]),
+        "When to color messages (default auto)",
+    ),
+    (
+        "--output",
+        None,
+        FlagArg::File,
+        "Write the --format report to a file",
+    ),
+    (
+        "--split-output",
+        None,
+        FlagArg::Dir,
+        "Write a JUnit report per file to a directory",
+    ),
+    (
+        "--fields",
+        None,
+        FlagArg::Text("a,b,..."),
+        "Fields for stats to tally",
+    ),
+    ("--profile", None, FlagArg::None, "Print timings to stderr"),
+];
+
+/// Subcommands completions offer in place of the first file.
+const SUBCOMMANDS: &[&str] = &["diff", "stats"];
+
+/// Shells `completions` can write a script for.
+const SHELLS: &[&str] = &["bash", "zsh", "fish"];
+
+/// Prints the usage summary for `--help`.
+fn print_help() {
+    println!("Validate .gitinfo files against the gitinfo JSON schema.\n");
+    println!("Usage:");
+    println!("  validate [options] [files...]   Validate files (default: .gitinfo)");
+    println!("  validate diff <old> <new>       Compare two files field by field");
+    println!("  validate stats [paths...]       Summarize fields across many files\n");
+    println!("Options:");
+    let usage = |flag: &str, short: Option<&str>, arg: &FlagArg| {
+        let names = match short {
+            Some(short) => format!("{}, {}", short, flag),
+            None => format!("    {}", flag),
+        };
+        match arg {
+            FlagArg::None => names,
+            FlagArg::File => format!("{} <file>", names),
+            FlagArg::Dir => format!("{} <dir>", names),
+            FlagArg::Choice(choices) => format!("{} <{}>", names, choices.join("|")),
+            FlagArg::Text(name) => format!("{} <{}>", names, name),
+        }
+    };
+    let lines: Vec<(String, &str)> = FLAGS
+        .iter()
+        .map(|(flag, short, arg, summary)| (usage(flag, *short, arg), *summary))
+        .collect();
+    let width = lines
+        .iter()
+        .map(|(usage, _)| usage.len())
+        .max()
+        .unwrap_or(0);
+    for (usage, summary) in lines {
+        println!("  {:width$}  {}", usage, summary, width = width);
+    }
+}
+
+/// The completion script for `shell`, for the binary named `bin`.
+fn completion_script(shell: &str, bin: &str) -> String {
+    let names = |wanted: fn(&FlagArg) -> bool| -> String {
+        let matching: Vec<&str> = FLAGS
+            .iter()
+            .filter(|(_, _, arg, _)| wanted(arg))
+            .map(|(flag, _, _, _)| *flag)
+            .collect();
+        matching.join("|")
+    };
+    let mut script = String::new();
+    match shell {
+        "bash" => {
+            let function = format!("_{}", bin.replace('-', "_"));
+            script.push_str(&format!("{}() {{\n", function));
+            script.push_str("    local cur=\"${COMP_WORDS[COMP_CWORD]}\" prev=\"${COMP_WORDS[COMP_CWORD-1]}\"\n");
+            script.push_str("    case \"$prev\" in\n");
+            script.push_str(&format!(
+                "        {}) COMPREPLY=($(compgen -f -- \"$cur\")); return ;;\n",
+                names(|a| matches!(a, FlagArg::File))
+            ));
+            script.push_str(&format!(
+                "        {}) COMPREPLY=($(compgen -d -- \"$cur\")); return ;;\n",
+                names(|a| matches!(a, FlagArg::Dir))
+            ));
+            for (flag, _, arg, _) in FLAGS {
+                if let FlagArg::Choice(choices) = arg {
+                    script.push_str(&format!(
+                        "        {}) COMPREPLY=($(compgen -W \"{}\" -- \"$cur\")); return ;;\n",
+                        flag,
+                        choices.join(" ")
+                    ));
+                }
+            }
+            script.push_str(&format!(
+                "        {}) return ;;\n",
+                names(|a| matches!(a, FlagArg::Text(_)))
+            ));
+            script.push_str("    esac\n");
+            let flags: Vec<&str> = FLAGS
+                .iter()
+                .flat_map(|(flag, short, _, _)| std::iter::once(*flag).chain(*short))
+                .collect();
+            script.push_str("    if [[ \"$cur\" == -* ]]; then\n");
+            script.push_str(&format!(
+                "        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\"))\n",
+                flags.join(" ")
+            ));
+            script.push_str("    elif [[ $COMP_CWORD -eq 1 ]]; then\n");
+            script.push_str(&format!(
+                "        COMPREPLY=($(compgen -W \"{}\" -- \"$cur\") $(compgen -f -- \"$cur\"))\n",
+                SUBCOMMANDS.join(" ")
+            ));
+            script.push_str("    else\n");
+            script.push_str("        COMPREPLY=($(compgen -f -- \"$cur\"))\n");
+            script.push_str("    fi\n");
+            script.push_str("}\n");
+            script.push_str(&format!("complete -o filenames -F {} {}\n", function, bin));
+        }
+        "zsh" => {
+            script.push_str(&format!("#compdef {}\n\n_arguments \\\n", bin));
+            for (flag, short, arg, summary) in FLAGS {
+                // Summaries avoid brackets, which zsh would need escaped
+                let value = match arg {
+                    FlagArg::None => String::new(),
+                    FlagArg::File => ":file:_files".to_string(),
+                    FlagArg::Dir => ":directory:_files -/".to_string(),
+                    FlagArg::Choice(choices) => format!(":value:({})", choices.join(" ")),
+                    FlagArg::Text(name) => format!(":{}: ", name),
+                };
+                let names = match short {
+                    Some(short) => format!("({} {})'{{{},{}}}'", short, flag, short, flag),
+                    None => flag.to_string(),
+                };
+                script.push_str(&format!(
+                    "  '{}[{}]{}' \\\n",
+                    names,
+                    summary.replace('\'', "'\\''"),
+                    value
+                ));
+            }
+            script.push_str(&format!(
+                "  '1:command or file:{{_alternative \"commands:command:({})\" \"files:file:_files\"}}' \\\n",
+                SUBCOMMANDS.join(" ")
+            ));
+            script.push_str("  '*:file:_files'\n");
+        }
+        _ => {
+            script.push_str(&format!(
+                "complete -c {} -n __fish_use_subcommand -a '{}'\n",
+                bin,
+                SUBCOMMANDS.join(" ")
+            ));
+            for (flag, short, arg, summary) in FLAGS {
+                let mut line = format!("complete -c {} -l {}", bin, &flag[2..]);
+                if let Some(short) = short {
+                    line.push_str(&format!(" -s {}", &short[1..]));
+                }
+                match arg {
+                    FlagArg::None => {}
+                    FlagArg::File => line.push_str(" -r -F"),
+                    FlagArg::Dir => line.push_str(" -x -a '(__fish_complete_directories)'"),
+                    FlagArg::Choice(choices) => {
+                        line.push_str(&format!(" -x -a '{}'", choices.join(" ")))
+                    }
+                    FlagArg::Text(_) => line.push_str(" -x"),
+                }
+                line.push_str(&format!(" -d '{}'\n", summary.replace('\'', "\\'")));
+                script.push_str(&line);
+            }
+        }
+    }
+    script
+}
+
+/// Returns the value following `flag`, or exits with a usage error.
+fn flag_value(args: &mut impl Iterator<Item = String>, flag: &str) -> String {
+    args.next()
+        .unwrap_or_else(|| usage_error(&format!("{} requires a value", flag)))
+}
+
+fn usage_error(message: &str) -> ! {
+    eprintln!("{}Error: {}{}", RED, message, NC);
+    process::exit(EXIT_ERROR);
+}
+
+/// Whether `auto` colors: `NO_COLOR` is unset or empty and both stdout
+/// and stderr are terminals.
+fn color_by_default() -> bool {
+    env::var_os("NO_COLOR").unwrap_or_default().is_empty()
+        && std::io::stdout().is_terminal()
+        && std::io::stderr().is_terminal()
+}
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let file_path = args.get(1).map(|s| s.as_str()).unwrap_or(".gitinfo");
+    let started = Instant::now();
+    // usage errors from parsing follow the auto rule
+    COLOR.store(color_by_default(), Ordering::Relaxed);
+    let mut options = parse_args(env::args().skip(1));
+    let color = match options.color {
+        ColorChoice::Always => true,
+        ColorChoice::Never => false,
+        ColorChoice::Auto => {
+            color_by_default() && options.format == Format::Text && options.output.is_none()
+        }
+    };
+    COLOR.store(color, Ordering::Relaxed);
+    if options.repair && !std::io::stdin().is_terminal() {
+        usage_error("--repair needs an interactive terminal");
+    }
+
+    if options.command == Command::Completions {
+        print!("{}", completion_script(&options.files[0], "validate"));
+        process::exit(EXIT_VALID);
+    }
+
+    // stats only parses, so it needs no schema
+    if options.command == Command::Stats {
+        process::exit(print_stats(&options));
+    }
+
+    let mut schema_paths: Vec<PathBuf> = options.schemas.iter().map(PathBuf::from).collect();
+    let mut source = "from --schema";
+    if schema_paths.is_empty() {
+        let (path, found) = default_schema_path();
+        schema_paths.push(path);
+        source = found;
+    }
+    if options.verbose {
+        for path in &schema_paths {
+            eprintln!("Using schema: {} ({})", absolute(path).display(), source);
+        }
+        for path in &options.merge_schemas {
+            eprintln!("Merging schema: {}", absolute(Path::new(path)).display());
+        }
+    }
+
+    let schema_started = Instant::now();
+    let mut loaded: Vec<NamedSchema> = schema_paths.iter().map(|p| load_schema(p)).collect();
+    let mut base = loaded.remove(0);
+    for path in &options.merge_schemas {
+        let overlay = load_schema(Path::new(path));
+        merge_schema(&mut base.schema, overlay.schema, options.replace_arrays);
+    }
+    if options.print_merged_schema {
+        println!("{}", serde_json::to_string_pretty(&base.schema).unwrap());
+        process::exit(EXIT_VALID);
+    }
+    let schemas = SchemaSet {
+        base,
+        overlays: loaded,
+    };
+
+    if options.workspace {
+        options.files = expand_dirs(&options.files);
+    }
+    let packages = options.workspace.then(|| index_packages(&options));
+
+    if let Some(rev) = &options.since {
+        options.files = changed_files(rev, &options.files);
+        if options.files.is_empty() {
+            println!("No .gitinfo files changed since {}", rev);
+            process::exit(EXIT_VALID);
+        }
+    }
+
+    if options.explain_schema {
+        print!("{}", schema_docs(&schemas.base.schema));
+        process::exit(EXIT_VALID);
+    }
+
+    if options.init {
+        process::exit(write_template(&options.files[0], &schemas.base.schema));
+    }
+
+    if options.test_schema {
+        let mut exit_code = EXIT_VALID;
+        for (named, schema_path) in schemas.all().zip(&schema_paths) {
+            let defects = test_schema(&named.schema, &named.dir, options.dialect);
+            if defects.is_empty() {
+                if !options.quiet {
+                    println!(
+                        "{}✓ {} passed self-test{}",
+                        GREEN,
+                        schema_path.display(),
+                        NC
+                    );
+                }
+                continue;
+            }
+            eprintln!(
+                "{}Schema self-test failed for {}:{}",
+                RED,
+                schema_path.display(),
+                NC
+            );
+            for defect in &defects {
+                eprintln!("  - {}", defect);
+            }
+            exit_code = EXIT_INVALID;
+        }
+        process::exit(exit_code);
+    }
+
+    if options.validate_schema_only {
+        let mut exit_code = EXIT_VALID;
+        for (named, schema_path) in schemas.all().zip(&schema_paths) {
+            let errors = meta_validate(&named.schema);
+            if errors.is_empty() {
+                if !options.quiet {
+                    println!(
+                        "{}✓ {} is a valid JSON Schema{}",
+                        GREEN,
+                        schema_path.display(),
+                        NC
+                    );
+                }
+                continue;
+            }
+            eprintln!(
+                "{}Schema meta-validation failed for {}:{}",
+                RED,
+                schema_path.display(),
+                NC
+            );
+            for error in &errors {
+                eprintln!("  - {}", error);
+            }
+            exit_code = EXIT_INVALID;
+        }
+        process::exit(exit_code);
+    }
+
+    let baseline = match &options.baseline {
+        Some(path) => Baseline::load(Path::new(path)).unwrap_or_else(|e| {
+            eprintln!("{}Error loading baseline {}: {}{}", RED, path, e, NC);
+            process::exit(EXIT_ERROR);
+        }),
+        None => Baseline::default(),
+    };
+    let mut session = Session {
+        baseline,
+        error_count: 0,
+        results: Vec::new(),
+        github: options.github_repo.as_deref().and_then(fetch_github_repo),
+        packages,
+        profile: Profile {
+            schema: schema_started.elapsed(),
+            ..Profile::default()
+        },
+    };
+    session.baseline.recording = options.write_baseline.is_some();
+
+    let exit_code = if options.command == Command::Diff {
+        let (a, b) = (&options.files[0], &options.files[1]);
+        diff_files(a, b, &schemas, &options, &mut session)
+    } else {
+        // The most severe outcome across all files decides the exit code
+        let mut exit_code = EXIT_VALID;
+        for file_path in &options.files {
+            let file_started = Instant::now();
+            exit_code = exit_code.max(check_file(file_path, &schemas, &options, &mut session));
+            session.profile.per_file.push((
+                display_name(file_path, &options).to_string(),
+                file_started.elapsed(),
+            ));
+            if options.fail_fast && exit_code != EXIT_VALID {
+                break;
+            }
+        }
+        exit_code
+    };
+
+    if options.count_only {
+        println!("{}", session.error_count);
+    }
+
+    if let Some(dir) = &options.split_output {
+        if let Err(e) = write_split_junit(Path::new(dir), &session.results) {
+            eprintln!("{}Error writing {}: {}{}", RED, dir, e, NC);
+            process::exit(EXIT_ERROR);
+        }
+    } else if !matches!(options.format, Format::Text | Format::Ndjson) {
+        let page = match options.format {
+            Format::Codeclimate => codeclimate_report(&session.results),
+            Format::Junit => junit_report(&session.results),
+            Format::GitlabAnnotations => gitlab_annotations(&session.results),
+            _ => html_report(&session.results),
+        };
+        match &options.output {
+            Some(path) => {
+                if let Err(e) = fs::write(path, page) {
+                    eprintln!("{}Error writing {}: {}{}", RED, path, e, NC);
+                    process::exit(EXIT_ERROR);
+                }
+            }
+            None => print!("{}", page),
+        }
+    }
+
+    if options.profile {
+        session.profile.print(started.elapsed());
+    }
+
+    if let Some(path) = &options.write_baseline {
+        if let Err(e) = session.baseline.write(Path::new(path)) {
+            eprintln!("{}Error writing baseline {}: {}{}", RED, path, e, NC);
+            process::exit(EXIT_ERROR);
+        }
+        println!(
+            "Wrote {} error(s) to baseline {}",
+            session.baseline.recorded.len(),
+            path
+        );
+    }
+
+    process::exit(exit_code);
+}
+
+/// A schema with the name used to prefix its errors when several are given.
+struct NamedSchema {
+    /// File stem without `.schema.json`, e.g. `org-overlay`.
+    name: String,
+    schema: Value,
+    /// Directory external `$ref`s resolve against.
+    dir: PathBuf,
+}
+
+/// The schema files validates against: the base, which a versioned or
+/// declared schema may replace, then overlays from repeated `--schema`.
+struct SchemaSet {
+    base: NamedSchema,
+    overlays: Vec<NamedSchema>,
+}
+
+impl SchemaSet {
+    fn all(&self) -> impl Iterator<Item = &NamedSchema> {
+        std::iter::once(&self.base).chain(&self.overlays)
+    }
+}
 
+/// Deep-merges `overlay` into `base` for `--merge-schema`. Objects merge
+/// key by key; arrays are appended to, skipping values the base already
+/// has, or with `replace_arrays` replaced; any other value in the overlay,
+/// `null` included, replaces the base's.
+fn merge_schema(base: &mut Value, overlay: Value, replace_arrays: bool) {
+    match (base, overlay) {
+        (Value::Object(base), Value::Object(overlay)) => {
+            for (key, value) in overlay {
+                match base.get_mut(&key) {
+                    Some(existing) => merge_schema(existing, value, replace_arrays),
+                    None => {
+                        base.insert(key, value);
+                    }
+                }
+            }
+        }
+        (Value::Array(base), Value::Array(overlay)) if !replace_arrays => {
+            for value in overlay {
+                if !base.contains(&value) {
+                    base.push(value);
+                }
+            }
+        }
+        (base, overlay) => *base = overlay,
+    }
+}
+
+/// Reads and parses a schema file, exiting on failure.
+fn load_schema(path: &Path) -> NamedSchema {
+    if !path.exists() {
+        eprintln!("{}Error: Schema not found: {}{}", RED, path.display(), NC);
+        process::exit(EXIT_ERROR);
+    }
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}Error reading schema: {}{}", RED, e, NC);
+            process::exit(EXIT_ERROR);
+        }
+    };
+    let schema: Value = match serde_json::from_str(&content) {
+        Ok(v) => v,
+        Err(e) => {
+            eprintln!("{}Error parsing schema: {}{}", RED, e, NC);
+            process::exit(EXIT_ERROR);
+        }
+    };
+    let file_name = path
+        .file_name()
+        .and_then(|n| n.to_str())
+        .unwrap_or("schema");
+    let name = file_name
+        .trim_end_matches(".json")
+        .trim_end_matches(".schema");
+    NamedSchema {
+        name: name.to_string(),
+        schema,
+        // External $refs resolve relative to the schema file
+        dir: path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+    }
+}
+
+/// `path` made absolute for display, or as given if it can't be resolved.
+fn absolute(path: &Path) -> PathBuf {
+    fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf())
+}
+
+/// Locates `gitinfo.schema.json` next to the repository checkout the
+/// executable was built in, falling back to paths relative to the CWD.
+/// Also returns which of those it picked, for `--verbose`.
+fn default_schema_path() -> (PathBuf, &'static str) {
     // Find schema path (two levels up from validators/rust/)
     let exe_path = env::current_exe().unwrap_or_default();
     let schema_path = exe_path
@@ -29,221 +1588,3316 @@ fn main() {
             Path::new("../../gitinfo.schema.json").to_path_buf()
         });
 
-    // Also try current working directory relative paths
-    let schema_path = if schema_path.exists() {
-        schema_path
-    } else {
-        let cwd_relative = Path::new("gitinfo.schema.json");
-        if cwd_relative.exists() {
-            cwd_relative.to_path_buf()
-        } else {
-            // Try from validators/rust/
-            Path::new("../../gitinfo.schema.json").to_path_buf()
-        }
-    };
+    // Also try current working directory relative paths
+    if schema_path.exists() {
+        (schema_path, "found relative to the executable")
+    } else {
+        let cwd_relative = Path::new("gitinfo.schema.json");
+        if cwd_relative.exists() {
+            (cwd_relative.to_path_buf(), "found in the current directory")
+        } else {
+            // Try from validators/rust/
+            (
+                Path::new("../../gitinfo.schema.json").to_path_buf(),
+                "fallback relative to the current directory",
+            )
+        }
+    }
+}
+
+/// Reads, parses and validates one input, printing the outcome.
+/// Returns the exit code for this file.
+fn check_file(
+    file_path: &str,
+    schemas: &SchemaSet,
+    options: &Options,
+    session: &mut Session,
+) -> i32 {
+    let (name, mut data, mut json, mut report) =
+        match load_and_validate(file_path, schemas, options, session) {
+            Ok(checked) => checked,
+            Err(code) => {
+                if options.format == Format::Ndjson {
+                    print_ndjson(&serde_json::json!({
+                        "file": display_name(file_path, options),
+                        "keyword": "parse",
+                        "message": "file could not be read or parsed",
+                        "severity": "error",
+                    }));
+                } else if options.format != Format::Text {
+                    session.results.push(FileResult {
+                        name: display_name(file_path, options).to_string(),
+                        valid: false,
+                        loaded: false,
+                        errors: Vec::new(),
+                        warnings: Vec::new(),
+                        omitted: 0,
+                        json: String::new(),
+                    });
+                }
+                return code;
+            }
+        };
+
+    if options.repair && repair_file(file_path, name, &report) {
+        eprintln!("Re-validating {}", name);
+        (_, data, json, report) = match load_and_validate(file_path, schemas, options, session) {
+            Ok(checked) => checked,
+            Err(code) => return code,
+        };
+    }
+
+    if options.normalize && normalize_file(file_path, name, &data, schemas, &report, options) {
+        eprintln!("Re-validating {}", name);
+        (_, data, json, report) = match load_and_validate(file_path, schemas, options, session) {
+            Ok(checked) => checked,
+            Err(code) => return code,
+        };
+    }
+
+    if options.fix {
+        fix_file(file_path, name, &data, &mut report, options);
+    }
+    if options.fix_format {
+        format_file(file_path, name, &report, options);
+    }
+    if let Some(repo) = &session.github {
+        compare_with_github(&mut report, &data, repo);
+    }
+    if let Some(packages) = &session.packages {
+        check_dependencies(&mut report, &data, packages);
+    }
+    let code = print_report(name, &data, &json, report, options, session);
+    if options.coverage {
+        print_coverage(&data, schemas, options);
+    }
+    code
+}
+
+/// Prints how many of the optional top-level properties, across the base
+/// schema and overlays, `data` sets, listing the unset ones with
+/// `--verbose`.
+fn print_coverage(data: &Value, schemas: &SchemaSet, options: &Options) {
+    let mut properties = BTreeSet::new();
+    let mut required = BTreeSet::new();
+    for named in schemas.all() {
+        if let Some(defined) = named.schema.get("properties").and_then(Value::as_object) {
+            properties.extend(defined.keys().map(String::as_str));
+        }
+        if let Some(listed) = named.schema.get("required").and_then(Value::as_array) {
+            required.extend(listed.iter().filter_map(Value::as_str));
+        }
+    }
+    let (set, unset): (Vec<&str>, Vec<&str>) = properties
+        .difference(&required)
+        .partition(|property| data.get(*property).is_some());
+    println!(
+        "Coverage: {}/{} optional fields set",
+        set.len(),
+        set.len() + unset.len()
+    );
+    if options.verbose && !unset.is_empty() {
+        println!("Unset optional fields:");
+        for property in unset {
+            println!("  .{}", property);
+        }
+    }
+}
+
+/// Loads a file and validates it, or each of its elements with `--each`.
+/// Returns the file's name, data, JSON text (comments blanked out) and
+/// report.
+fn load_and_validate<'a>(
+    file_path: &'a str,
+    schemas: &SchemaSet,
+    options: &'a Options,
+    session: &mut Session,
+) -> Result<(&'a str, Value, String, Report), i32> {
+    let (name, data, json, text) = load_file(file_path, options, &mut session.profile)?;
+
+    let phase = Instant::now();
+    let report = if options.each {
+        let Some(elements) = data.as_array() else {
+            eprintln!("{}Error: {}: --each expects a JSON array{}", RED, name, NC);
+            return Err(EXIT_INVALID);
+        };
+        validate_each(name, file_path, elements, schemas, options)
+    } else {
+        validate_data(name, file_path, &data, schemas, options)
+    };
+    let mut report = report?;
+    if options.require_comments && !options.each {
+        check_comments(&mut report, &text, &json);
+    }
+    session.profile.validate += phase.elapsed();
+    Ok((name, data, json, report))
+}
+
+/// Reports each top-level property of `json` whose key isn't preceded by a
+/// comment in `text`, the same input before stripping: a `//` or `/* */`
+/// comment on the line above, or a block comment before it on its line.
+fn check_comments(report: &mut Report, text: &str, json: &str) {
+    for (key, offset) in top_level_keys(json) {
+        let line_start = text[..offset].rfind('\n').map_or(0, |i| i + 1);
+        let before_key = text[line_start..offset].trim();
+        let line_above = text[..line_start.saturating_sub(1)]
+            .lines()
+            .next_back()
+            .unwrap_or_default()
+            .trim();
+        let commented = before_key.starts_with("/*")
+            || line_above.starts_with("//")
+            || line_above.ends_with("*/");
+        if !commented {
+            report.errors.push(ValidationError::new(
+                "",
+                "comments",
+                format!("property \"{}\" has no explanatory comment", key),
+            ));
+        }
+    }
+}
+
+/// The keys of the top-level object in `json`, with the byte offset of
+/// each key's opening quote.
+fn top_level_keys(json: &str) -> Vec<(String, usize)> {
+    let bytes = json.as_bytes();
+    let mut keys = Vec::new();
+    let mut depth = 0;
+    let mut i = 0;
+    while i < bytes.len() {
+        match bytes[i] {
+            b'{' | b'[' => depth += 1,
+            b'}' | b']' => depth -= 1,
+            b'"' => {
+                let start = i;
+                i += 1;
+                while i < bytes.len() && bytes[i] != b'"' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                let is_key = depth == 1
+                    && json
+                        .get(i + 1..)
+                        .is_some_and(|rest| rest.trim_start().starts_with(':'));
+                if is_key {
+                    if let Ok(key) = serde_json::from_str::<String>(&json[start..=i]) {
+                        keys.push((key, start));
+                    }
+                }
+            }
+            _ => {}
+        }
+        i += 1;
+    }
+    keys
+}
+
+/// Validates each element of a top-level array, prefixing error paths with
+/// the element's index.
+fn validate_each(
+    name: &str,
+    file_path: &str,
+    elements: &[Value],
+    schemas: &SchemaSet,
+    options: &Options,
+) -> Result<Report, i32> {
+    let mut combined = Report::default();
+    for (i, element) in elements.iter().enumerate() {
+        if options.fail_fast && !combined.errors.is_empty() {
+            break;
+        }
+        let prefix = format!("[{}]", i);
+        let label = format!("{}{}", name, prefix);
+        let report = validate_data(&label, file_path, element, schemas, options)?;
+        combined
+            .errors
+            .extend(report.errors.into_iter().map(|mut error| {
+                error.path.insert_str(0, &prefix);
+                error
+            }));
+        combined
+            .warnings
+            .extend(report.warnings.into_iter().map(
+                |warning| match warning.strip_prefix("root:") {
+                    Some(rest) => format!("{}:{}", prefix, rest),
+                    None => format!("{}{}", prefix, warning),
+                },
+            ));
+        combined.read_only.extend(
+            report
+                .read_only
+                .iter()
+                .map(|path| format!("{}{}", prefix, path)),
+        );
+    }
+    Ok(combined)
+}
+
+/// Applies the suggested fixes in `report` to the file on disk, dropping
+/// the errors they fix. A value is only rewritten when its JSON string
+/// literal appears exactly once in the file, so comments and formatting
+/// survive and an ambiguous match is left alone.
+fn fix_file(file_path: &str, name: &str, data: &Value, report: &mut Report, options: &Options) {
+    if !report.errors.iter().any(|e| e.suggestion.is_some()) && report.aliases.is_empty() {
+        return;
+    }
+    if file_path == "-" {
+        eprintln!("{}Warning: --fix can't rewrite stdin{}", YELLOW, NC);
+        return;
+    }
+    let mut content = match fs::read_to_string(file_path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}Warning: can't fix {}: {}{}", YELLOW, name, e, NC);
+            return;
+        }
+    };
+
+    let renamed = rename_aliases(&mut content, data, report, options);
+    let mut fixed = 0;
+    report.errors.retain(|error| {
+        let Some(suggestion) = &error.suggestion else {
+            return true;
+        };
+        let Some(old) = value_at_path(data, &error.path).and_then(|v| v.as_str()) else {
+            return true;
+        };
+        let old = Value::from(old).to_string();
+        if content.matches(&old).count() != 1 {
+            return true;
+        }
+        content = content.replacen(&old, &Value::from(suggestion.as_str()).to_string(), 1);
+        fixed += 1;
+        false
+    });
+
+    if fixed == 0 && renamed == 0 {
+        return;
+    }
+    if let Err(e) = fs::write(file_path, content) {
+        eprintln!("{}Error writing {}: {}{}", RED, name, e, NC);
+        process::exit(EXIT_ERROR);
+    }
+    if fixed > 0 {
+        eprintln!("{}Fixed {} value(s) in {}{}", YELLOW, fixed, name, NC);
+    }
+    if renamed > 0 {
+        eprintln!(
+            "{}Renamed {} aliased key(s) in {}{}",
+            YELLOW, renamed, name, NC
+        );
+    }
+}
+
+/// Renames the keys `report` found under an `x-aliases` name to the
+/// property's current name, in place like `normalize_file`, and drops
+/// their warnings. A key is left alone when the current name is set too.
+/// Returns how many were renamed.
+fn rename_aliases(
+    content: &mut String,
+    data: &Value,
+    report: &mut Report,
+    options: &Options,
+) -> usize {
+    let json = input_json(content, options);
+    let mut keys: Vec<(usize, &Alias)> = report
+        .aliases
+        .iter()
+        .filter(|a| value_at_path(data, &a.path).is_some_and(|o| o.get(&a.name).is_none()))
+        .filter_map(|a| {
+            let value = value_offset(&json, &format!("{}.{}", a.path, a.alias))?;
+            let key = Value::from(a.alias.as_str()).to_string();
+            Some((json[..value].rfind(&key)?, a))
+        })
+        .collect();
+    // Replace from the end so earlier offsets stay put
+    keys.sort_by_key(|(start, _)| std::cmp::Reverse(*start));
+    for (start, a) in &keys {
+        let old_len = Value::from(a.alias.as_str()).to_string().len();
+        content.replace_range(
+            *start..start + old_len,
+            &Value::from(a.name.as_str()).to_string(),
+        );
+    }
+    let renamed: Vec<String> = keys.iter().map(|(_, a)| a.to_string()).collect();
+    report
+        .warnings
+        .retain(|w| !renamed.iter().any(|r| w.ends_with(r.as_str())));
+    renamed.len()
+}
+
+/// Rewrites the `x-normalize` fields in `report` to their normalized
+/// values, in place so comments and formatting survive. The file is left
+/// alone when the result would fail checks that `data` passes. Returns
+/// whether the file changed.
+fn normalize_file(
+    file_path: &str,
+    name: &str,
+    data: &Value,
+    schemas: &SchemaSet,
+    report: &Report,
+    options: &Options,
+) -> bool {
+    if report.normalized.is_empty() {
+        return false;
+    }
+    if file_path == "-" {
+        eprintln!("{}Warning: --normalize can't rewrite stdin{}", YELLOW, NC);
+        return false;
+    }
+    let mut content = match fs::read_to_string(file_path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}Warning: can't normalize {}: {}{}", YELLOW, name, e, NC);
+            return false;
+        }
+    };
+
+    // Replace from the end so earlier offsets stay put
+    let json = input_json(&content, options);
+    let mut literals: Vec<(usize, usize, &Normalization)> = report
+        .normalized
+        .iter()
+        .filter_map(|n| {
+            let start = value_offset(&json, &n.path)?;
+            Some((start, string_end(&json, start)?, n))
+        })
+        .collect();
+    literals.sort_by_key(|(start, _, _)| std::cmp::Reverse(*start));
+    for (start, end, n) in &literals {
+        content.replace_range(start..end, &Value::from(n.value.as_str()).to_string());
+    }
+
+    let normalized_json = input_json(&content, options);
+    let Ok(normalized) = parse_input(&content, &normalized_json, options) else {
+        return false;
+    };
+    let (Ok(before), Ok(after)) = (
+        validate_data(name, file_path, data, schemas, options),
+        validate_data(name, file_path, &normalized, schemas, options),
+    ) else {
+        return false;
+    };
+    let introduced: Vec<&ValidationError> = after
+        .errors
+        .iter()
+        .filter(|e| {
+            !before
+                .errors
+                .iter()
+                .any(|b| b.path == e.path && b.keyword == e.keyword && b.message == e.message)
+        })
+        .collect();
+    if !introduced.is_empty() {
+        eprintln!(
+            "{}Not normalizing {}: it would introduce {} error(s){}",
+            YELLOW,
+            name,
+            introduced.len(),
+            NC
+        );
+        for error in introduced {
+            eprintln!("  - {}", error);
+        }
+        return false;
+    }
+
+    if let Err(e) = fs::write(file_path, content) {
+        eprintln!("{}Error writing {}: {}{}", RED, name, e, NC);
+        process::exit(EXIT_ERROR);
+    }
+    eprintln!(
+        "{}Normalized {} value(s) in {}{}",
+        YELLOW,
+        literals.len(),
+        name,
+        NC
+    );
+    true
+}
+
+/// The offset just past the string literal starting at `start` in `json`.
+fn string_end(json: &str, start: usize) -> Option<usize> {
+    let bytes = json.as_bytes();
+    if bytes.get(start) != Some(&b'"') {
+        return None;
+    }
+    let mut i = start + 1;
+    while *bytes.get(i)? != b'"' {
+        i += if bytes[i] == b'\\' { 2 } else { 1 };
+    }
+    Some(i + 1)
+}
+
+/// Prompts on the terminal to rename or delete each unknown property, then
+/// writes the edited file. Returns whether the file changed. Like `--fix`,
+/// edits are textual, so a key is only touched when it appears once.
+fn repair_file(file_path: &str, name: &str, report: &Report) -> bool {
+    let unknown: Vec<&ValidationError> = report
+        .errors
+        .iter()
+        .filter(|e| e.property.is_some())
+        .collect();
+    if unknown.is_empty() {
+        return false;
+    }
+    if file_path == "-" {
+        eprintln!("{}Warning: --repair can't rewrite stdin{}", YELLOW, NC);
+        return false;
+    }
+    let mut content = match fs::read_to_string(file_path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}Warning: can't repair {}: {}{}", YELLOW, name, e, NC);
+            return false;
+        }
+    };
+
+    let mut changed = false;
+    for error in unknown {
+        let key = error.property.as_deref().unwrap_or_default();
+        let choices = match &error.suggestion {
+            Some(suggestion) => format!("[r]ename to \"{}\", [d]elete, [s]kip", suggestion),
+            None => "[d]elete, [s]kip".to_string(),
+        };
+        eprint!(
+            "{}: unknown property \"{}\" at {}. {}? ",
+            name,
+            key,
+            display_path(&error.path),
+            choices
+        );
+        let mut answer = String::new();
+        if std::io::stdin().read_line(&mut answer).is_err() {
+            break;
+        }
+        let edited = match (answer.trim().to_lowercase().as_str(), &error.suggestion) {
+            ("r" | "rename", Some(suggestion)) => rename_key(&mut content, key, suggestion),
+            ("d" | "delete", _) => delete_key(&mut content, key),
+            _ => continue,
+        };
+        if edited {
+            changed = true;
+        } else {
+            eprintln!(
+                "{}Warning: \"{}\" doesn't appear exactly once in {}; edit it by hand{}",
+                YELLOW, key, name, NC
+            );
+        }
+    }
+
+    if !changed {
+        return false;
+    }
+    if let Err(e) = fs::write(file_path, content) {
+        eprintln!("{}Error writing {}: {}{}", RED, name, e, NC);
+        process::exit(EXIT_ERROR);
+    }
+    true
+}
+
+/// Rewrites a valid file with `--indent` spaces per level, sorting keys
+/// with `--sort-keys`. Keys and values keep their exact spelling; comments
+/// are dropped. An invalid file is left untouched.
+fn format_file(file_path: &str, name: &str, report: &Report, options: &Options) {
+    if !report.is_valid(options) {
+        eprintln!(
+            "{}Not formatting {}: fix its validation errors first{}",
+            YELLOW, name, NC
+        );
+        return;
+    }
+    if file_path == "-" {
+        eprintln!("{}Warning: --fix-format can't rewrite stdin{}", YELLOW, NC);
+        return;
+    }
+    // Read again, since --fix may have just rewritten the file
+    let content = match fs::read_to_string(file_path) {
+        Ok(content) => content,
+        Err(e) => {
+            eprintln!("{}Warning: can't format {}: {}{}", YELLOW, name, e, NC);
+            return;
+        }
+    };
+    let json = match options.input_format {
+        InputFormat::Jsonc => strip_jsonc(&content),
+        _ => content.clone(),
+    };
+    let mut pos = 0;
+    let Some(node) = JsonNode::parse(&json, &mut pos) else {
+        return;
+    };
+    let mut formatted = String::new();
+    node.write(&mut formatted, options.indent, 0, options.sort_keys);
+    formatted.push('\n');
+    if formatted == content {
+        return;
+    }
+    if let Err(e) = fs::write(file_path, formatted) {
+        eprintln!("{}Error writing {}: {}{}", RED, name, e, NC);
+        process::exit(EXIT_ERROR);
+    }
+    eprintln!("{}Formatted {}{}", YELLOW, name, NC);
+}
+
+/// JSON text split into its structure, keeping scalars and keys exactly as
+/// written so reformatting can't change a value's spelling.
+enum JsonNode<'j> {
+    Scalar(&'j str),
+    Array(Vec<JsonNode<'j>>),
+    /// Members as (decoded key, key as written, value).
+    Object(Vec<(String, &'j str, JsonNode<'j>)>),
+}
+
+impl<'j> JsonNode<'j> {
+    /// Parses the value starting at `pos`, advancing past it.
+    fn parse(json: &'j str, pos: &mut usize) -> Option<Self> {
+        *pos = skip_whitespace(json, *pos);
+        let bytes = json.as_bytes();
+        match bytes.get(*pos)? {
+            b'[' => {
+                *pos += 1;
+                let mut items = Vec::new();
+                loop {
+                    *pos = skip_whitespace(json, *pos);
+                    if *bytes.get(*pos)? == b']' {
+                        *pos += 1;
+                        return Some(JsonNode::Array(items));
+                    }
+                    items.push(JsonNode::parse(json, pos)?);
+                    *pos = skip_whitespace(json, *pos);
+                    if bytes.get(*pos) == Some(&b',') {
+                        *pos += 1;
+                    }
+                }
+            }
+            b'{' => {
+                *pos += 1;
+                let mut members = Vec::new();
+                loop {
+                    *pos = skip_whitespace(json, *pos);
+                    if *bytes.get(*pos)? == b'}' {
+                        *pos += 1;
+                        return Some(JsonNode::Object(members));
+                    }
+                    let mut keys =
+                        serde_json::Deserializer::from_str(&json[*pos..]).into_iter::<String>();
+                    let key = keys.next()?.ok()?;
+                    let raw_key = &json[*pos..*pos + keys.byte_offset()];
+                    *pos = skip_whitespace(json, *pos + keys.byte_offset());
+                    if *bytes.get(*pos)? != b':' {
+                        return None;
+                    }
+                    *pos += 1;
+                    members.push((key, raw_key, JsonNode::parse(json, pos)?));
+                    *pos = skip_whitespace(json, *pos);
+                    if bytes.get(*pos) == Some(&b',') {
+                        *pos += 1;
+                    }
+                }
+            }
+            _ => {
+                let mut values = serde_json::Deserializer::from_str(&json[*pos..])
+                    .into_iter::<serde::de::IgnoredAny>();
+                values.next()?.ok()?;
+                let raw = &json[*pos..*pos + values.byte_offset()];
+                *pos += values.byte_offset();
+                Some(JsonNode::Scalar(raw.trim_end()))
+            }
+        }
+    }
+
+    /// Writes the node at nesting `depth`, in the layout `serde_json`'s
+    /// pretty printer uses.
+    fn write(&self, out: &mut String, indent: usize, depth: usize, sort_keys: bool) {
+        let newline = |out: &mut String, depth: usize| {
+            out.push('\n');
+            out.push_str(&" ".repeat(indent * depth));
+        };
+        match self {
+            JsonNode::Scalar(raw) => out.push_str(raw),
+            JsonNode::Array(items) if items.is_empty() => out.push_str("[]"),
+            JsonNode::Object(members) if members.is_empty() => out.push_str("{}"),
+            JsonNode::Array(items) => {
+                out.push('[');
+                for (i, item) in items.iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, depth + 1);
+                    item.write(out, indent, depth + 1, sort_keys);
+                }
+                newline(out, depth);
+                out.push(']');
+            }
+            JsonNode::Object(members) => {
+                let mut members: Vec<_> = members.iter().collect();
+                if sort_keys {
+                    members.sort_by(|a, b| a.0.cmp(&b.0));
+                }
+                out.push('{');
+                for (i, (_, raw_key, value)) in members.into_iter().enumerate() {
+                    if i > 0 {
+                        out.push(',');
+                    }
+                    newline(out, depth + 1);
+                    out.push_str(raw_key);
+                    out.push_str(": ");
+                    value.write(out, indent, depth + 1, sort_keys);
+                }
+                newline(out, depth);
+                out.push('}');
+            }
+        }
+    }
+}
+
+/// Byte range of the `"key":` in `content`, if it appears exactly once.
+fn key_span(content: &str, key: &str) -> Option<(usize, usize)> {
+    let literal = Value::from(key).to_string();
+    let re = Regex::new(&format!(r"{}\s*:", regex::escape(&literal))).unwrap();
+    let mut matches = re.find_iter(content);
+    let found = matches.next()?;
+    matches
+        .next()
+        .is_none()
+        .then(|| (found.start(), found.end()))
+}
+
+fn rename_key(content: &mut String, key: &str, new_key: &str) -> bool {
+    let Some((start, _)) = key_span(content, key) else {
+        return false;
+    };
+    let old_len = Value::from(key).to_string().len();
+    content.replace_range(start..start + old_len, &Value::from(new_key).to_string());
+    true
+}
+
+/// Removes the `"key": value` member and the comma that separated it.
+fn delete_key(content: &mut String, key: &str) -> bool {
+    let Some((mut start, value_start)) = key_span(content, key) else {
+        return false;
+    };
+    // Parse just the value to find where it ends
+    let rest = &content[value_start..];
+    let mut values = serde_json::Deserializer::from_str(rest).into_iter::<Value>();
+    if !matches!(values.next(), Some(Ok(_))) {
+        return false;
+    }
+    let mut end = value_start + values.byte_offset();
+
+    let after = &content[end..];
+    if after.trim_start().starts_with(',') {
+        // Take the following comma, and the rest of the line if blank
+        end += after.find(',').unwrap() + 1;
+        let line_rest = &content[end..];
+        let blank = line_rest.len() - line_rest.trim_start_matches([' ', '\t']).len();
+        if content[end + blank..].starts_with('\n') {
+            end += blank + 1;
+            let line_start = content[..start].trim_end_matches([' ', '\t']).len();
+            if line_start == 0 || content[..line_start].ends_with('\n') {
+                start = line_start;
+            }
+        }
+    } else if let Some(comma) = content[..start].trim_end().strip_suffix(',') {
+        // Last member: take the comma before it instead
+        start = comma.len();
+    }
+    content.replace_range(start..end, "");
+    true
+}
+
+/// Looks up a data path such as `.maintainers[0][1]` in `data`.
+fn value_at_path<'v>(data: &'v Value, path: &str) -> Option<&'v Value> {
+    path_segments(path)
+        .into_iter()
+        .try_fold(data, |value, segment| match segment {
+            PathSegment::Index(i) => value.get(i),
+            PathSegment::Key(key) => value.get(key),
+        })
+}
+
+/// Validates `data` against the base schema and any overlays, merging
+/// their reports.
+fn validate_data(
+    name: &str,
+    file_path: &str,
+    data: &Value,
+    schemas: &SchemaSet,
+    options: &Options,
+) -> Result<Report, i32> {
+    let report = validate_base(name, file_path, data, &schemas.base, options)?;
+    let mut report = apply_overlays(report, data, schemas, options);
+    if !options.require_fields.is_empty() {
+        check_require_fields(&mut report, data, options);
+    }
+    if !options.repo_hosts.is_empty() {
+        check_repo_host(&mut report, data, options);
+    }
+    if let Some(limit) = options.max_logo_bytes {
+        check_logo_size(&mut report, data, limit);
+    }
+    if options.check_files {
+        check_local_files(&mut report, file_path, options);
+    }
+    if options.check_git {
+        check_language(&mut report, data, file_path, options);
+        check_authors(&mut report, data, file_path, options);
+    }
+    Ok(report)
+}
+
+/// Reports the `--require-fields` missing from `data`, ahead of the other
+/// errors as root-level checks come first.
+fn check_require_fields(report: &mut Report, data: &Value, options: &Options) {
+    let Some(obj) = data.as_object() else {
+        return;
+    };
+    let missing = options
+        .require_fields
+        .iter()
+        .filter(|field| !obj.contains_key(field.as_str()))
+        .map(|field| {
+            ValidationError::new(
+                "",
+                "required",
+                format!(
+                    "missing required property \"{}\" (required by --require-fields)",
+                    field
+                ),
+            )
+        });
+    report.errors.splice(0..0, missing);
+}
+
+/// Reports a `root` URL that isn't on one of the `--repo-host-allowlist`
+/// hosts, or under its path prefix when one is given.
+fn check_repo_host(report: &mut Report, data: &Value, options: &Options) {
+    let Some(root) = data.get("root").and_then(Value::as_str) else {
+        return;
+    };
+    let location = repo_location(root);
+    let allowed = options.repo_hosts.iter().any(|entry| {
+        let (host, path) = entry.split_once('/').unwrap_or((entry, ""));
+        let (their_host, their_path) = location.split_once('/').unwrap_or((&location, ""));
+        host.eq_ignore_ascii_case(their_host)
+            && (path.is_empty()
+                || their_path == path
+                || their_path
+                    .strip_prefix(path)
+                    .is_some_and(|rest| rest.starts_with('/')))
+    });
+    if !allowed {
+        report.errors.push(ValidationError::new(
+            ".root",
+            "allowlist",
+            "host not in allowlist".to_string(),
+        ));
+    }
+}
+
+/// Reports an inline `data:` icon whose decoded payload is over `limit`
+/// bytes.
+fn check_logo_size(report: &mut Report, data: &Value, limit: u64) {
+    let Some(message) = data
+        .get("icon")
+        .and_then(Value::as_str)
+        .and_then(|icon| oversized_data_uri(icon, limit))
+    else {
+        return;
+    };
+    report
+        .errors
+        .push(ValidationError::new(".icon", "maxLogoBytes", message));
+}
+
+/// The host and path of a repository URL, without the scheme, credentials,
+/// port, query or trailing `/`, e.g. `github.com/org/repo.git`.
+fn repo_location(url: &str) -> String {
+    let rest = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let rest = rest.split(['?', '#']).next().unwrap_or_default();
+    let (authority, path) = rest.split_once('/').unwrap_or((rest, ""));
+    let host = authority
+        .rsplit_once('@')
+        .map_or(authority, |(_, host)| host);
+    let host = host.split(':').next().unwrap_or_default();
+    format!("{}/{}", host, path.trim_end_matches('/'))
+        .trim_end_matches('/')
+        .to_string()
+}
+
+/// The directory relative paths in `file_path` resolve against: its parent,
+/// or the current directory for stdin without `--stdin-filename`.
+fn base_dir<'a>(file_path: &'a str, options: &'a Options) -> &'a Path {
+    let file_path = match file_path {
+        "-" => options.stdin_filename.as_deref().unwrap_or("-"),
+        path => path,
+    };
+    match file_path {
+        "-" => Path::new("."),
+        path => Path::new(path).parent().unwrap_or(Path::new(".")),
+    }
+}
+
+/// Checks the `x-localPath` fields in `report` against the disk, resolving
+/// them against the directory of the validated file.
+fn check_local_files(report: &mut Report, file_path: &str, options: &Options) {
+    let base = base_dir(file_path, options);
+    for file in std::mem::take(&mut report.local_files) {
+        if let Some(message) = local_file_problem(&base.join(&file.value), &file) {
+            report.errors.push(ValidationError {
+                schema: file.schema,
+                ..ValidationError::new(&file.path, "x-localPath", message)
+            });
+        }
+    }
+}
+
+/// File extensions that suggest each language for `--check-git`, matched
+/// ignoring case. `--language-extensions` adds to or replaces entries.
+const LANGUAGE_EXTENSIONS: &[(&str, &[&str])] = &[
+    ("C", &["c", "h"]),
+    ("C#", &["cs"]),
+    ("C++", &["cpp", "cc", "cxx", "hpp", "hh"]),
+    ("Go", &["go"]),
+    ("Java", &["java"]),
+    ("JavaScript", &["js", "mjs", "cjs", "jsx"]),
+    ("Kotlin", &["kt", "kts"]),
+    ("PHP", &["php"]),
+    ("Python", &["py"]),
+    ("Ruby", &["rb"]),
+    ("Rust", &["rs"]),
+    ("Shell", &["sh", "bash"]),
+    ("Swift", &["swift"]),
+    ("TypeScript", &["ts", "tsx"]),
+];
+
+/// Files `--check-git` looks at before giving up on finding a language.
+const LANGUAGE_SAMPLE_LIMIT: usize = 10_000;
+
+/// Warns when a declared `language` (a string, or an array of them) has no
+/// files with a matching extension in the repository around `file_path`.
+/// Languages without known extensions aren't checked.
+fn check_language(report: &mut Report, data: &Value, file_path: &str, options: &Options) {
+    let declared: Vec<&str> = match data.get("language") {
+        Some(Value::String(language)) => vec![language.as_str()],
+        Some(Value::Array(items)) => items.iter().filter_map(Value::as_str).collect(),
+        _ => return,
+    };
+    let mut found = BTreeSet::new();
+    let mut budget = LANGUAGE_SAMPLE_LIMIT;
+    sample_extensions(base_dir(file_path, options), &mut found, &mut budget);
+    for language in declared {
+        let extensions: Vec<String> = match options
+            .language_extensions
+            .iter()
+            .rev()
+            .find(|(name, _)| name.eq_ignore_ascii_case(language))
+        {
+            Some((_, extensions)) => extensions.clone(),
+            None => match LANGUAGE_EXTENSIONS
+                .iter()
+                .find(|(name, _)| name.eq_ignore_ascii_case(language))
+            {
+                Some((_, extensions)) => extensions.iter().map(|e| e.to_string()).collect(),
+                None => continue,
+            },
+        };
+        if extensions.is_empty() || extensions.iter().any(|e| found.contains(&e.to_lowercase())) {
+            continue;
+        }
+        let listed: Vec<String> = extensions.iter().map(|e| format!(".{}", e)).collect();
+        report.warnings.push(format!(
+            ".language: declared \"{}\" but no {} files found",
+            language,
+            listed.join(" or ")
+        ));
+    }
+}
+
+/// Commits `--check-git` reads author emails from, newest first.
+const AUTHOR_COMMIT_LIMIT: usize = 1000;
+
+/// Warns when an email declared in `maintainers` (`[name, email]` pairs)
+/// or a top-level `authors` array (emails, `Name <email>` strings or
+/// objects with an `email`) isn't the author of any of the repository's
+/// last `AUTHOR_COMMIT_LIMIT` commits. Emails are compared ignoring case,
+/// after `.mailmap`.
+fn check_authors(report: &mut Report, data: &Value, file_path: &str, options: &Options) {
+    let mut declared: Vec<(String, &str)> = Vec::new();
+    if let Some(maintainers) = data.get("maintainers").and_then(Value::as_array) {
+        for (i, maintainer) in maintainers.iter().enumerate() {
+            if let Some(email) = maintainer.get(1).and_then(Value::as_str) {
+                declared.push((format!(".maintainers[{}][1]", i), email));
+            }
+        }
+    }
+    if let Some(authors) = data.get("authors").and_then(Value::as_array) {
+        for (i, author) in authors.iter().enumerate() {
+            match author {
+                Value::String(author) => {
+                    let email = match author.rfind('<') {
+                        Some(start) => author[start + 1..].trim_end().trim_end_matches('>'),
+                        None => author.trim(),
+                    };
+                    declared.push((format!(".authors[{}]", i), email));
+                }
+                author => {
+                    if let Some(email) = author.get("email").and_then(Value::as_str) {
+                        declared.push((format!(".authors[{}].email", i), email));
+                    }
+                }
+            }
+        }
+    }
+    declared.retain(|(_, email)| email.contains('@'));
+    if declared.is_empty() {
+        return;
+    }
+    let committers = match git_author_emails(base_dir(file_path, options)) {
+        Ok(committers) => committers,
+        Err(e) => {
+            eprintln!(
+                "{}Warning: --check-git can't read the history of {}: {}{}",
+                YELLOW, file_path, e, NC
+            );
+            return;
+        }
+    };
+    for (path, email) in declared {
+        if !committers.contains(&email.to_lowercase()) {
+            report.warnings.push(format!(
+                "{}: \"{}\" has no commits in this repository",
+                path, email
+            ));
+        }
+    }
+}
+
+/// The lowercased author emails of the last `AUTHOR_COMMIT_LIMIT` commits
+/// of the repository containing `dir`.
+fn git_author_emails(dir: &Path) -> Result<BTreeSet<String>, String> {
+    let output = process::Command::new("git")
+        .arg("-C")
+        .arg(dir)
+        .args(["log", "--format=%aE"])
+        .arg(format!("--max-count={}", AUTHOR_COMMIT_LIMIT))
+        .output()
+        .map_err(|e| format!("cannot run git: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.lines().next().unwrap_or_default().trim().to_string());
+    }
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    Ok(stdout.lines().map(str::to_lowercase).collect())
+}
+
+/// Collects the lowercased extensions of files under `dir`, skipping the
+/// directories `find_gitinfo_files` skips, until `budget` files are seen.
+fn sample_extensions(dir: &Path, found: &mut BTreeSet<String>, budget: &mut usize) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries.filter_map(Result::ok) {
+        if *budget == 0 {
+            return;
+        }
+        let path = entry.path();
+        let name = entry.file_name();
+        let name = name.to_str().unwrap_or_default();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" && name != "node_modules" {
+                sample_extensions(&path, found, budget);
+            }
+        } else {
+            *budget -= 1;
+            if let Some(extension) = path.extension().and_then(|e| e.to_str()) {
+                found.insert(extension.to_lowercase());
+            }
+        }
+    }
+}
+
+/// Describes what's wrong with the file at `path`, if anything. Images need
+/// a known image extension and, for the binary formats, a matching header.
+fn local_file_problem(path: &Path, file: &LocalFile) -> Option<String> {
+    if !path.is_file() {
+        return Some(format!("\"{}\" does not exist", file.value));
+    }
+    if !file.image {
+        return None;
+    }
+    let extension = path
+        .extension()
+        .and_then(|e| e.to_str())
+        .map(|e| e.to_ascii_lowercase());
+    let Some(format) = extension.as_deref().and_then(image_format) else {
+        return Some(format!("\"{}\" is not a recognized image file", file.value));
+    };
+    // SVG is text, so look for the root element a little way in
+    let mut header = Vec::new();
+    let read = fs::File::open(path).and_then(|f| f.take(4096).read_to_end(&mut header));
+    if read.is_ok() && !image_header_matches(format, &header) {
+        return Some(format!(
+            "\"{}\" doesn't contain {} image data",
+            file.value, format
+        ));
+    }
+    None
+}
+
+/// Maps an image file extension to the format it implies.
+fn image_format(extension: &str) -> Option<&'static str> {
+    match extension {
+        "png" => Some("PNG"),
+        "jpg" | "jpeg" => Some("JPEG"),
+        "webp" => Some("WebP"),
+        "svg" => Some("SVG"),
+        _ => None,
+    }
+}
+
+/// Whether `header`, the start of a file, carries `format`'s signature.
+fn image_header_matches(format: &str, header: &[u8]) -> bool {
+    match format {
+        "PNG" => header.starts_with(b"\x89PNG\r\n\x1a\n"),
+        "JPEG" => header.starts_with(&[0xff, 0xd8, 0xff]),
+        "WebP" => header.len() >= 12 && &header[..4] == b"RIFF" && &header[8..12] == b"WEBP",
+        _ => String::from_utf8_lossy(header).contains("<svg"),
+    }
+}
+
+/// Merges the overlays' reports into the base schema's `report`, tagging
+/// each error with the schema that found it. Without overlays the report
+/// is returned untouched.
+fn apply_overlays(
+    mut report: Report,
+    data: &Value,
+    schemas: &SchemaSet,
+    options: &Options,
+) -> Report {
+    if schemas.overlays.is_empty() || (options.fail_fast && !report.errors.is_empty()) {
+        return report;
+    }
+    report.tag(&schemas.base.name);
+    for overlay in &schemas.overlays {
+        if options.fail_fast && !report.errors.is_empty() {
+            break;
+        }
+        let mut layer = Report::new(data, &overlay.schema, &overlay.dir, options);
+        layer.tag(&overlay.name);
+        report.merge(layer);
+    }
+    report
+}
+
+/// Picks the base schema for `data` (versioned, declared or default) and
+/// validates against it. `name` labels messages about the schema choice.
+fn validate_base(
+    name: &str,
+    file_path: &str,
+    data: &Value,
+    base: &NamedSchema,
+    options: &Options,
+) -> Result<Report, i32> {
+    let (schema, schema_dir) = (&base.schema, &base.dir);
+    // A versioned schema directory takes precedence over other sources
+    if let Some(dir) = &options.schema_dir {
+        let dir = Path::new(dir);
+        match versioned_schema(data, dir) {
+            Ok(Some(versioned)) => return Ok(Report::new(data, &versioned, dir, options)),
+            Ok(None) => {}
+            Err(e) => {
+                eprintln!("{}Error: {}: {}{}", RED, name, e, NC);
+                return Err(EXIT_ERROR);
+            }
+        }
+    }
+
+    let declared = data.get("$schema").and_then(|s| s.as_str());
+    match declared {
+        Some(declared) if options.use_declared_schema => {
+            match load_declared_schema(declared, file_path, options) {
+                Ok((declared_schema, declared_dir)) => {
+                    Ok(Report::new(data, &declared_schema, &declared_dir, options))
+                }
+                Err(e) => {
+                    eprintln!(
+                        "{}Error loading declared schema {}: {}{}",
+                        RED, declared, e, NC
+                    );
+                    Err(EXIT_ERROR)
+                }
+            }
+        }
+        _ => {
+            let used_id = schema.get("$id").and_then(|id| id.as_str());
+            if let (Some(declared), Some(used_id)) = (declared, used_id) {
+                if declared.trim_end_matches('#') != used_id.trim_end_matches('#') {
+                    eprintln!(
+                        "{}Warning: {} declares $schema {} but was validated against {}{}",
+                        YELLOW, name, declared, used_id, NC
+                    );
+                }
+            }
+            Ok(Report::new(data, schema, schema_dir, options))
+        }
+    }
+}
+
+/// Loads `gitinfo.v<N>.schema.json` from `dir` for the file's integer
+/// `schemaVersion`. Files without a `schemaVersion` get `None` and use the
+/// default schema.
+fn versioned_schema(data: &Value, dir: &Path) -> Result<Option<Value>, String> {
+    let Some(version) = data.get("schemaVersion") else {
+        return Ok(None);
+    };
+    let version = version.as_u64().ok_or_else(|| {
+        format!(
+            "schemaVersion must be a non-negative integer, got {}",
+            version
+        )
+    })?;
+    let path = dir.join(format!("gitinfo.v{}.schema.json", version));
+    if !path.exists() {
+        return Err(format!(
+            "no schema for schemaVersion {} in {}",
+            version,
+            dir.display()
+        ));
+    }
+    let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+    serde_json::from_str(&content)
+        .map(Some)
+        .map_err(|e| format!("cannot parse {}: {}", path.display(), e))
+}
+
+/// Loads the schema a file declares in `$schema`: a URL is fetched when the
+/// network is allowed, anything else is a path relative to the file.
+/// Returns the schema and the directory its relative `$ref`s resolve against.
+fn load_declared_schema(
+    declared: &str,
+    file_path: &str,
+    options: &Options,
+) -> Result<(Value, PathBuf), String> {
+    let (content, dir) = if declared.starts_with("http://") || declared.starts_with("https://") {
+        let content = match &options.schema_cache {
+            Some(cache) if options.offline => read_cached_schema(Path::new(cache), declared)?,
+            _ if !options.allow_network => {
+                return Err("it is a URL; pass --allow-network to fetch it".to_string())
+            }
+            Some(cache) => fetch_cached_schema(Path::new(cache), declared)?,
+            None => fetch_url(declared)?,
+        };
+        (content, PathBuf::from("."))
+    } else {
+        let base = match file_path {
+            "-" => Path::new("."),
+            path => Path::new(path).parent().unwrap_or(Path::new(".")),
+        };
+        let path = base.join(declared);
+        let content = fs::read_to_string(&path).map_err(|e| e.to_string())?;
+        (
+            content,
+            path.parent().unwrap_or(Path::new(".")).to_path_buf(),
+        )
+    };
+    let schema = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+    Ok((schema, dir))
+}
+
+/// Downloads `url` with `curl`, which keeps an HTTP stack out of the binary.
+fn fetch_url(url: &str) -> Result<String, String> {
+    let output = process::Command::new("curl")
+        .args(["-fsSL", "--max-time", "30", url])
+        .output()
+        .map_err(|e| format!("cannot run curl: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.trim().to_string());
+    }
+    String::from_utf8(output.stdout).map_err(|e| e.to_string())
+}
+
+/// Where the `--schema-from-url-cache` directory keeps `url`: the body and
+/// a `.meta.json` with its `ETag` and `Last-Modified`, named by a hash of
+/// the URL.
+fn cache_paths(cache: &Path, url: &str) -> (PathBuf, PathBuf) {
+    let key = format!("{:016x}", fnv1a(url));
+    (
+        cache.join(format!("{}.json", key)),
+        cache.join(format!("{}.meta.json", key)),
+    )
+}
+
+/// The cached copy of `url`, for `--offline`.
+fn read_cached_schema(cache: &Path, url: &str) -> Result<String, String> {
+    let (body, _) = cache_paths(cache, url);
+    fs::read_to_string(body).map_err(|_| {
+        format!(
+            "it isn't in the schema cache {} (--offline)",
+            cache.display()
+        )
+    })
+}
+
+/// Fetches `url` through the cache: a cached copy is revalidated with a
+/// conditional GET and reused when the server answers 304 Not Modified, or,
+/// with a warning, when the request fails.
+fn fetch_cached_schema(cache: &Path, url: &str) -> Result<String, String> {
+    fs::create_dir_all(cache).map_err(|e| format!("cannot create {}: {}", cache.display(), e))?;
+    let (body_path, meta_path) = cache_paths(cache, url);
+    let cached = fs::read_to_string(&body_path).ok();
+    let meta: Value = fs::read_to_string(&meta_path)
+        .ok()
+        .and_then(|m| serde_json::from_str(&m).ok())
+        .unwrap_or_default();
+
+    let headers_path = body_path.with_extension("headers.tmp");
+    let download_path = body_path.with_extension("download.tmp");
+    let mut command = process::Command::new("curl");
+    command.args(["-fsSL", "--max-time", "30", "-w", "%{http_code}", "-D"]);
+    command.arg(&headers_path).arg("-o").arg(&download_path);
+    if cached.is_some() {
+        if let Some(etag) = meta.get("etag").and_then(Value::as_str) {
+            command.arg("-H").arg(format!("If-None-Match: {}", etag));
+        }
+        if let Some(modified) = meta.get("last_modified").and_then(Value::as_str) {
+            command
+                .arg("-H")
+                .arg(format!("If-Modified-Since: {}", modified));
+        }
+    }
+    let fetched = command
+        .arg(url)
+        .output()
+        .map_err(|e| format!("cannot run curl: {}", e))
+        .and_then(|output| {
+            if output.status.success() {
+                Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+            } else {
+                Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+            }
+        });
+    let headers = fs::read_to_string(&headers_path).unwrap_or_default();
+    let _ = fs::remove_file(&headers_path);
+
+    let status = match (fetched, cached) {
+        (Ok(status), Some(cached)) if status == "304" => {
+            let _ = fs::remove_file(&download_path);
+            return Ok(cached);
+        }
+        (Ok(status), _) => status,
+        (Err(e), Some(cached)) => {
+            eprintln!(
+                "{}Warning: can't refresh {} ({}); using the cached copy{}",
+                YELLOW, url, e, NC
+            );
+            return Ok(cached);
+        }
+        (Err(e), None) => return Err(e),
+    };
+
+    let body = fs::read_to_string(&download_path).map_err(|e| e.to_string())?;
+    if status == "200" {
+        // With redirects followed, the last response's headers apply
+        let last = headers
+            .trim_end()
+            .rsplit("\r\n\r\n")
+            .next()
+            .unwrap_or_default();
+        let header = |name: &str| {
+            last.lines().find_map(|line| {
+                let (key, value) = line.split_once(':')?;
+                key.trim()
+                    .eq_ignore_ascii_case(name)
+                    .then(|| value.trim().to_string())
+            })
+        };
+        let meta = serde_json::json!({
+            "url": url,
+            "etag": header("etag"),
+            "last_modified": header("last-modified"),
+        });
+        fs::rename(&download_path, &body_path).map_err(|e| e.to_string())?;
+        fs::write(&meta_path, meta.to_string()).map_err(|e| e.to_string())?;
+    } else {
+        let _ = fs::remove_file(&download_path);
+    }
+    Ok(body)
+}
+
+/// Fetches `repo`'s public metadata from the GitHub API, or the one
+/// `GITHUB_API_URL` names (as set on GitHub Enterprise runners). Failures,
+/// such as being offline or rate limited, only warn: the comparison is
+/// skipped.
+fn fetch_github_repo(repo: &str) -> Option<Value> {
+    let api = env::var("GITHUB_API_URL").unwrap_or_else(|_| "https://api.github.com".to_string());
+    let url = format!("{}/repos/{}", api.trim_end_matches('/'), repo);
+    let fetched = fetch_url(&url)
+        .and_then(|body| serde_json::from_str::<Value>(&body).map_err(|e| e.to_string()));
+    match fetched {
+        Ok(metadata) if metadata.is_object() => Some(metadata),
+        Ok(_) => None,
+        Err(e) => {
+            eprintln!(
+                "{}Warning: can't fetch GitHub metadata for {} ({}); skipping the comparison{}",
+                YELLOW, repo, e, NC
+            );
+            None
+        }
+    }
+}
+
+/// Warns where `data` disagrees with GitHub's `repo` metadata on the
+/// description, homepage, topics or license. A field missing on either
+/// side isn't compared.
+fn compare_with_github(report: &mut Report, data: &Value, repo: &Value) {
+    let declared = |field: &str| data.get(field).and_then(Value::as_str).map(str::trim);
+    let upstream = |field: &str| {
+        repo.get(field)
+            .and_then(Value::as_str)
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+    };
+
+    if let (Some(ours), Some(theirs)) = (declared("description"), upstream("description")) {
+        if ours != theirs {
+            report.warnings.push(format!(
+                ".description: \"{}\" differs from GitHub's \"{}\"",
+                ours, theirs
+            ));
+        }
+    }
+
+    if let (Some(ours), Some(theirs)) = (declared("homepage"), upstream("homepage")) {
+        if ours.trim_end_matches('/') != theirs.trim_end_matches('/') {
+            report.warnings.push(format!(
+                ".homepage: \"{}\" differs from GitHub's \"{}\"",
+                ours, theirs
+            ));
+        }
+    }
+
+    // GitHub lowercases topics, so tags compare ignoring case
+    let lowercased = |list: Option<&Value>| -> Option<BTreeSet<String>> {
+        let items = list?.as_array()?;
+        Some(
+            items
+                .iter()
+                .filter_map(Value::as_str)
+                .map(str::to_lowercase)
+                .collect(),
+        )
+    };
+    let topics = lowercased(repo.get("topics")).filter(|topics| !topics.is_empty());
+    if let (Some(tags), Some(topics)) = (lowercased(data.get("tags")), topics) {
+        let only_here: Vec<&str> = tags.difference(&topics).map(String::as_str).collect();
+        let only_github: Vec<&str> = topics.difference(&tags).map(String::as_str).collect();
+        if !only_here.is_empty() || !only_github.is_empty() {
+            report.warnings.push(format!(
+                ".tags: differ from GitHub's topics (only here: [{}]; only on GitHub: [{}])",
+                only_here.join(", "),
+                only_github.join(", ")
+            ));
+        }
+    }
+
+    // GitHub reports NOASSERTION for licenses it can't identify
+    let license = repo
+        .get("license")
+        .and_then(|l| l.get("spdx_id"))
+        .and_then(Value::as_str)
+        .filter(|id| *id != "NOASSERTION");
+    if let (Some(ours), Some(theirs)) = (declared("license"), license) {
+        if !ours.eq_ignore_ascii_case(theirs) {
+            report.warnings.push(format!(
+                ".license: \"{}\" differs from GitHub's \"{}\"",
+                ours, theirs
+            ));
+        }
+    }
+}
+
+/// The `.gitinfo` files changed between `rev` and `HEAD`, limited to
+/// `given` (with directories expanded) when paths were named. If git can't
+/// answer, warns and falls back to those files, or every `.gitinfo` under
+/// the current directory; exits when there's nothing to fall back on.
+fn changed_files(rev: &str, given: &[String]) -> Vec<String> {
+    let wanted = expand_dirs(given);
+    let changed = match git_changed_files(rev) {
+        Ok(changed) => changed,
+        Err(e) => {
+            let files = if given.is_empty() {
+                find_gitinfo_files(Path::new("."))
+            } else {
+                wanted
+            };
+            if files.is_empty() {
+                eprintln!(
+                    "{}Error: can't list changes since {} ({}), and there are no .gitinfo files to validate instead{}",
+                    RED, rev, e, NC
+                );
+                process::exit(EXIT_ERROR);
+            }
+            eprintln!(
+                "{}Warning: can't list changes since {} ({}); validating all files{}",
+                YELLOW, rev, e, NC
+            );
+            return files;
+        }
+    };
+    changed
+        .into_iter()
+        .filter(|path| path.ends_with(".gitinfo") && Path::new(path).exists())
+        .filter(|path| given.is_empty() || wanted.iter().any(|w| Path::new(w) == Path::new(path)))
+        .collect()
+}
+
+/// Paths changed on this branch since `rev`, relative to the current
+/// directory.
+fn git_changed_files(rev: &str) -> Result<Vec<String>, String> {
+    let output = process::Command::new("git")
+        .args(["diff", "--name-only", "--relative"])
+        .arg(format!("{}...HEAD", rev))
+        .output()
+        .map_err(|e| format!("cannot run git: {}", e))?;
+    if !output.status.success() {
+        // git follows the reason with a usage dump; keep the first line
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(stderr.lines().next().unwrap_or_default().trim().to_string());
+    }
+    let stdout = String::from_utf8(output.stdout).map_err(|e| e.to_string())?;
+    Ok(stdout.lines().map(str::to_string).collect())
+}
+
+/// `paths` with each directory replaced by the `.gitinfo` files under it.
+fn expand_dirs(paths: &[String]) -> Vec<String> {
+    let mut files = Vec::new();
+    for path in paths {
+        if Path::new(path).is_dir() {
+            files.extend(find_gitinfo_files(Path::new(path)));
+        } else {
+            files.push(path.clone());
+        }
+    }
+    files
+}
+
+/// The top-level `name` of each file, for `--workspace`. Files that can't
+/// be read or parsed are skipped here and reported when validated; stdin
+/// can only be read once, so it isn't indexed.
+fn index_packages(options: &Options) -> BTreeSet<String> {
+    let mut packages = BTreeSet::new();
+    for file_path in options.files.iter().filter(|path| *path != "-") {
+        let Ok(content) = fs::read_to_string(file_path) else {
+            continue;
+        };
+        let content = if options.normalize_quotes {
+            normalize_quotes(&content).0
+        } else {
+            content
+        };
+        let json = input_json(&content, options);
+        if let Ok(data) = parse_input(&content, &json, options) {
+            if let Some(name) = data.get("name").and_then(Value::as_str) {
+                packages.insert(name.to_string());
+            }
+        }
+    }
+    packages
+}
+
+/// Reports each `dependencies` entry that names no package in the
+/// workspace.
+fn check_dependencies(report: &mut Report, data: &Value, packages: &BTreeSet<String>) {
+    let Some(dependencies) = data.get("dependencies").and_then(Value::as_array) else {
+        return;
+    };
+    for (i, dependency) in dependencies.iter().enumerate() {
+        let Some(name) = dependency.as_str() else {
+            continue;
+        };
+        if !packages.contains(name) {
+            report.errors.push(ValidationError::new(
+                &format!(".dependencies[{}]", i),
+                "workspace",
+                format!("unknown package \"{}\" not found in workspace", name),
+            ));
+        }
+    }
+}
+
+/// Every `.gitinfo` file under `dir`, skipping hidden, `target` and
+/// `node_modules` directories.
+fn find_gitinfo_files(dir: &Path) -> Vec<String> {
+    let mut found = Vec::new();
+    let Ok(entries) = fs::read_dir(dir) else {
+        return found;
+    };
+    let mut entries: Vec<PathBuf> = entries.filter_map(|e| e.ok().map(|e| e.path())).collect();
+    entries.sort();
+    for path in entries {
+        let name = path
+            .file_name()
+            .and_then(|n| n.to_str())
+            .unwrap_or_default();
+        if path.is_dir() {
+            if !name.starts_with('.') && name != "target" && name != "node_modules" {
+                found.extend(find_gitinfo_files(&path));
+            }
+        } else if name.ends_with(".gitinfo") {
+            found.push(
+                path.strip_prefix("./")
+                    .unwrap_or(&path)
+                    .display()
+                    .to_string(),
+            );
+        }
+    }
+    found
+}
+
+/// The name diagnostics use for `file_path`; stdin is `<stdin>` unless
+/// `--stdin-filename` names it.
+fn display_name<'a>(file_path: &'a str, options: &'a Options) -> &'a str {
+    match file_path {
+        "-" => options.stdin_filename.as_deref().unwrap_or("<stdin>"),
+        path => path,
+    }
+}
+
+/// Reads one input and runs it through the parsing pipeline, printing any
+/// error. Returns the display name, parsed data, JSON text and the text as
+/// read (before comments are stripped), or the exit code.
+fn load_file<'a>(
+    file_path: &'a str,
+    options: &'a Options,
+    profile: &mut Profile,
+) -> Result<(&'a str, Value, String, String), i32> {
+    let from_stdin = file_path == "-";
+    let file_path = display_name(file_path, options);
+
+    if !from_stdin && !Path::new(file_path).exists() {
+        eprintln!("{}Error: File not found: {}{}", RED, file_path, NC);
+        return Err(EXIT_ERROR);
+    }
+
+    // Shell globs expand to several explicit paths; only nag about a
+    // single file the user named directly. Arrays read with --each are
+    // collections, not .gitinfo files themselves.
+    if !from_stdin && !options.any_name && !options.each && options.files.len() == 1 {
+        let name = Path::new(file_path).file_name().and_then(|n| n.to_str());
+        if name != Some(".gitinfo") {
+            eprintln!(
+                "{}Warning: {} is not named .gitinfo; git tooling won't pick it up (use --any-name to silence){}",
+                YELLOW, file_path, NC
+            );
+        }
+    }
+
+    // Read and parse .gitinfo file (with JSONC comment stripping)
+    profile.files += 1;
+    if options.stream {
+        return stream_file(file_path, from_stdin, options, profile);
+    }
+    let phase = Instant::now();
+    let file_content = if from_stdin {
+        let mut c = String::new();
+        std::io::stdin().read_to_string(&mut c).map(|_| c)
+    } else {
+        fs::read_to_string(file_path)
+    };
+    let file_content = match file_content {
+        Ok(c) => c,
+        Err(e) => {
+            eprintln!("{}Error reading file: {}{}", RED, e, NC);
+            return Err(EXIT_ERROR);
+        }
+    };
+
+    let file_content = if options.normalize_quotes {
+        let (normalized, count) = normalize_quotes(&file_content);
+        if count > 0 {
+            eprintln!(
+                "{}Warning: replaced {} typographic quote(s) in {}{}",
+                YELLOW, count, file_path, NC
+            );
+        }
+        normalized
+    } else {
+        file_content
+    };
+    profile.read += phase.elapsed();
+
+    let phase = Instant::now();
+    let json = input_json(&file_content, options);
+    profile.strip += phase.elapsed();
+
+    let phase = Instant::now();
+    let parsed = parse_input(&file_content, &json, options);
+    profile.parse += phase.elapsed();
+
+    match parsed {
+        Ok(data) => Ok((file_path, data, json, file_content)),
+        Err((offset, message)) => {
+            print_parse_error(file_path, &file_content, offset, &message);
+            Err(EXIT_INVALID)
+        }
+    }
+}
+
+/// Parses a file with `--stream`, comments and trailing commas stripped as
+/// it's read. Nothing is kept to quote, so a parse error gives only its
+/// position, and the texts returned are empty, as for TOML and YAML.
+fn stream_file<'a>(
+    file_path: &'a str,
+    from_stdin: bool,
+    options: &Options,
+    profile: &mut Profile,
+) -> Result<(&'a str, Value, String, String), i32> {
+    let phase = Instant::now();
+    let input: Box<dyn Read> = if from_stdin {
+        Box::new(std::io::stdin())
+    } else {
+        match fs::File::open(file_path) {
+            Ok(file) => Box::new(file),
+            Err(e) => {
+                eprintln!("{}Error reading file: {}{}", RED, e, NC);
+                return Err(EXIT_ERROR);
+            }
+        }
+    };
+    let parsed = match options.input_format {
+        InputFormat::Json => serde_json::from_reader(std::io::BufReader::new(input)),
+        _ => parse_jsonc_reader(input),
+    };
+    profile.parse += phase.elapsed();
+
+    match parsed {
+        Ok(data) if options.sort_keys => {
+            Ok((file_path, sort_keys(data), String::new(), String::new()))
+        }
+        Ok(data) => Ok((file_path, data, String::new(), String::new())),
+        Err(e) if e.is_io() => {
+            eprintln!("{}Error reading file: {}{}", RED, e, NC);
+            Err(EXIT_ERROR)
+        }
+        Err(e) => {
+            // Block comments lose their newlines, so after one the line
+            // may be off
+            eprintln!(
+                "{}{}:{}:{}: parse error: {}{}",
+                RED,
+                file_path,
+                e.line(),
+                e.column(),
+                strip_location(e.to_string()),
+                NC
+            );
+            Err(EXIT_INVALID)
+        }
+    }
+}
+
+/// The JSON text to parse for JSON input: the content with JSONC comments
+/// blanked out. Empty for TOML and YAML.
+fn input_json(content: &str, options: &Options) -> String {
+    // Strict JSON must already be free of comments and trailing commas
+    match options.input_format {
+        InputFormat::Jsonc => strip_jsonc(content),
+        InputFormat::Json => content.to_string(),
+        InputFormat::Toml | InputFormat::Yaml => String::new(),
+    }
+}
+
+/// Parses a file's `content` (or, for JSON, its `json` from `input_json`).
+/// On failure, returns the byte offset of the problem and the message.
+fn parse_input(content: &str, json: &str, options: &Options) -> Result<Value, (usize, String)> {
+    match options.input_format {
+        InputFormat::Json | InputFormat::Jsonc => serde_json::from_str(json).map_err(|e| {
+            let offset = json
+                .split_inclusive('\n')
+                .take(e.line().saturating_sub(1))
+                .map(str::len)
+                .sum::<usize>()
+                + e.column().saturating_sub(1);
+            (offset, strip_location(e.to_string()))
+        }),
+        InputFormat::Toml => toml::from_str::<toml::Value>(content)
+            .map(toml_to_json)
+            .map_err(|e| {
+                let offset = e.span().map_or(0, |span| span.start);
+                // toml puts the expected tokens on a second line
+                (offset, e.message().trim_end().replace('\n', "; "))
+            }),
+        InputFormat::Yaml => serde_yaml::from_str(content).map_err(|e| {
+            let offset = e.location().map_or(0, |l| l.index());
+            (offset, strip_location(e.to_string()))
+        }),
+    }
+    .map(|data| {
+        if options.sort_keys {
+            sort_keys(data)
+        } else {
+            data
+        }
+    })
+}
+
+/// Drops the ` at line L column C` positions a parser puts in its messages;
+/// the position is printed separately.
+fn strip_location(message: String) -> String {
+    let re = Regex::new(r" at line \d+ column \d+").unwrap();
+    re.replace_all(&message, "").into_owned()
+}
+
+/// Converts parsed TOML to JSON. Datetimes, which JSON lacks, become their
+/// TOML text.
+fn toml_to_json(value: toml::Value) -> Value {
+    match value {
+        toml::Value::String(s) => Value::String(s),
+        toml::Value::Integer(i) => Value::from(i),
+        toml::Value::Float(f) => Value::from(f),
+        toml::Value::Boolean(b) => Value::Bool(b),
+        toml::Value::Datetime(d) => Value::String(d.to_string()),
+        toml::Value::Array(items) => items.into_iter().map(toml_to_json).collect(),
+        toml::Value::Table(table) => table
+            .into_iter()
+            .map(|(key, value)| (key, toml_to_json(value)))
+            .collect(),
+    }
+}
+
+/// Prints a parse error as `file:line:column: parse error: ...` followed by
+/// the offending line and a caret. `offset` is the error's byte offset in
+/// the text the parser saw; JSONC stripping keeps byte offsets but not lines
+/// (block comments lose their newlines), so the position is mapped back to
+/// `original` through the offset.
+fn print_parse_error(file_path: &str, original: &str, offset: usize, message: &str) {
+    let mut offset = offset.min(original.len());
+    while !original.is_char_boundary(offset) {
+        offset -= 1;
+    }
+    let line_start = original[..offset].rfind('\n').map_or(0, |i| i + 1);
+    let line_end = original[offset..]
+        .find('\n')
+        .map_or(original.len(), |i| offset + i);
+    let line = original[..offset].matches('\n').count() + 1;
+    let before = &original[line_start..offset];
+    let column = before.chars().count() + 1;
+
+    eprintln!(
+        "{}{}:{}:{}: parse error: {}{}",
+        RED, file_path, line, column, message, NC
+    );
+    let text = original[line_start..line_end].trim_end_matches('\r');
+    // Keep tabs so the caret lines up under the same indentation
+    let padding: String = before
+        .chars()
+        .map(|c| if c == '\t' { '\t' } else { ' ' })
+        .collect();
+    eprintln!("  {}", text);
+    eprintln!("  {}^", padding);
+}
+
+/// The outcome of validating one value.
+#[derive(Default)]
+struct Report {
+    errors: Vec<ValidationError>,
+    warnings: Vec<String>,
+    /// Errors dropped by `--max-errors`; set only when printing.
+    omitted: usize,
+    /// Errors outside `--print-errors-only-for`; set only when printing.
+    hidden: usize,
+    /// Paths of `readOnly` fields present in the data.
+    read_only: Vec<String>,
+    /// Relative paths to check with `--check-files`.
+    local_files: Vec<LocalFile>,
+    /// Values for `--normalize` to rewrite.
+    normalized: Vec<Normalization>,
+    /// Keys set under an `x-aliases` name, for `--fix` to rename.
+    aliases: Vec<Alias>,
+}
+
+impl Report {
+    /// Validates `data` against `schema` with the command-line settings.
+    /// Errors aren't capped here, so a baseline can filter them first.
+    fn new(data: &Value, schema: &Value, schema_dir: &Path, options: &Options) -> Self {
+        let mut validator = Validator::new(schema, schema_dir);
+        validator.no_edit_readonly = options.no_edit_readonly;
+        validator.check_cross_fields = options.check_cross_fields;
+        validator.fail_fast = options.fail_fast;
+        validator.max_depth = options.max_depth;
+        if let Some(dialect) = options.dialect {
+            validator.dialect = dialect;
+        }
+        validator.warn_empty_strings = options.warn_empty_strings;
+        if options.warn_placeholders {
+            validator.placeholders = match &options.placeholders {
+                Some(placeholders) => placeholders.clone(),
+                None => DEFAULT_PLACEHOLDERS.iter().map(|p| p.to_string()).collect(),
+            };
+        }
+        validator.describe_enums = options.verbose;
+        match &options.only {
+            Some(pointer) => {
+                if let Err(e) = validator.validate_only(data, pointer) {
+                    eprintln!("{}Error: --only {}: {}{}", RED, pointer, e, NC);
+                    process::exit(EXIT_ERROR);
+                }
+            }
+            None => validator.validate(data),
+        }
+        // Without --strict, x-severity "warning" failures are only warnings
+        let (downgraded, errors): (Vec<ValidationError>, Vec<ValidationError>) = validator
+            .errors
+            .into_iter()
+            .partition(|e| e.severity == Severity::Warning && !options.strict);
+        let mut warnings = validator.warnings;
+        warnings.extend(downgraded.iter().map(|e| e.to_string()));
+        Report {
+            errors,
+            warnings,
+            omitted: 0,
+            hidden: 0,
+            read_only: validator.read_only,
+            local_files: validator.local_files,
+            normalized: validator.normalized,
+            aliases: validator.aliases,
+        }
+    }
+
+    /// Labels errors and warnings with the schema they came from.
+    fn tag(&mut self, schema_name: &str) {
+        for error in &mut self.errors {
+            error.source = Some(schema_name.to_string());
+        }
+        for warning in &mut self.warnings {
+            warning.insert_str(0, &format!("[{}] ", schema_name));
+        }
+    }
+
+    /// Adds another schema's findings. An error both schemas report is
+    /// kept once, tagged with both names.
+    fn merge(&mut self, other: Report) {
+        for error in other.errors {
+            let duplicate = self.errors.iter_mut().find(|e| {
+                e.path == error.path && e.keyword == error.keyword && e.message == error.message
+            });
+            match (duplicate, error.source) {
+                (Some(existing), Some(source)) => {
+                    let sources = existing.source.get_or_insert_with(String::new);
+                    sources.push_str(", ");
+                    sources.push_str(&source);
+                }
+                (Some(_), None) => {}
+                (None, source) => self.errors.push(ValidationError { source, ..error }),
+            }
+        }
+        self.warnings.extend(other.warnings);
+        for path in other.read_only {
+            if !self.read_only.contains(&path) {
+                self.read_only.push(path);
+            }
+        }
+        for file in other.local_files {
+            if !self.local_files.iter().any(|f| f.path == file.path) {
+                self.local_files.push(file);
+            }
+        }
+        for normalization in other.normalized {
+            if !self.normalized.iter().any(|n| n.path == normalization.path) {
+                self.normalized.push(normalization);
+            }
+        }
+        for alias in other.aliases {
+            if !self
+                .aliases
+                .iter()
+                .any(|a| a.path == alias.path && a.alias == alias.alias)
+            {
+                self.aliases.push(alias);
+            }
+        }
+    }
+
+    /// Whether the value passed; under `--strict` any warning fails it.
+    fn is_valid(&self, options: &Options) -> bool {
+        self.errors.is_empty()
+            && self.omitted == 0
+            && self.hidden == 0
+            && (self.warnings.is_empty() || !options.strict)
+    }
+}
+
+/// Prints a validation report for a file. Returns the exit code.
+fn print_report(
+    file_path: &str,
+    data: &Value,
+    json: &str,
+    mut report: Report,
+    options: &Options,
+    session: &mut Session,
+) -> i32 {
+    let baseline = &mut session.baseline;
+    // Record before suppressing so a rewritten baseline keeps old entries
+    if baseline.recording {
+        baseline.record(file_path, &report.errors);
+    }
+    let suppressed = baseline.suppress(file_path, &mut report.errors);
+    session.error_count += report.errors.len();
+
+    // Errors just written to a baseline are accepted
+    let recording = baseline.recording;
+    let exit = |valid: bool| {
+        if valid || recording {
+            EXIT_VALID
+        } else {
+            EXIT_INVALID
+        }
+    };
+
+    if options.count_only {
+        return exit(report.is_valid(options));
+    }
+
+    if options.sort_keys {
+        // Stable sort: errors at the same path keep their keyword order
+        report.errors.sort_by_cached_key(|e| path_segments(&e.path));
+        report.warnings.sort();
+    }
+
+    if let Some(prefix) = &options.errors_only_for {
+        let before = report.errors.len();
+        report.errors.retain(|e| path_within(&e.path, prefix));
+        report.hidden = before - report.errors.len();
+    }
+
+    if let Some(max) = options.max_errors {
+        if report.errors.len() > max {
+            report.omitted = report.errors.len() - max;
+            report.errors.truncate(max);
+        }
+    }
+
+    if let Some(pointer) = &options.explain_error {
+        explain_error(data, pointer, &report.errors);
+        return exit(report.is_valid(options));
+    }
+
+    if options.format == Format::Ndjson {
+        for error in &report.errors {
+            let mut line = serde_json::to_value(error).unwrap();
+            line["file"] = Value::from(file_path);
+            print_ndjson(&line);
+        }
+        for warning in &report.warnings {
+            print_ndjson(&serde_json::json!({
+                "file": file_path,
+                "message": warning,
+                "severity": "warning",
+            }));
+        }
+        return exit(report.is_valid(options));
+    }
+
+    if options.format != Format::Text {
+        let valid = report.is_valid(options);
+        session.results.push(FileResult {
+            name: file_path.to_string(),
+            valid,
+            loaded: true,
+            errors: report.errors,
+            warnings: report.warnings,
+            omitted: report.omitted + report.hidden,
+            json: json.to_string(),
+        });
+        return exit(valid);
+    }
+
+    if options.verbose {
+        print_fields(data, &report.read_only);
+    }
 
-    if !Path::new(file_path).exists() {
-        eprintln!("{}Error: File not found: {}{}", RED, file_path, NC);
-        process::exit(1);
+    if !report.warnings.is_empty() {
+        eprintln!("{}Warnings for {}:{}", YELLOW, file_path, NC);
+        for warning in &report.warnings {
+            eprintln!("  - {}", warning);
+        }
     }
 
-    if !schema_path.exists() {
+    if suppressed > 0 {
         eprintln!(
-            "{}Error: Schema not found: {}{}",
-            RED,
-            schema_path.display(),
-            NC
+            "{}{} known error(s) in {} suppressed by baseline{}",
+            YELLOW, suppressed, file_path, NC
         );
-        process::exit(1);
     }
 
-    // Read and parse schema
-    let schema_content = match fs::read_to_string(&schema_path) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("{}Error reading schema: {}{}", RED, e, NC);
-            process::exit(1);
+    if !report.is_valid(options) {
+        eprintln!("{}Validation failed for {}:{}", RED, file_path, NC);
+        for error in &report.errors {
+            if options.trace {
+                eprintln!("  - [{}] {}", error.keyword, error);
+            } else {
+                eprintln!("  - {}", error);
+            }
         }
-    };
-    let schema: Value = match serde_json::from_str(&schema_content) {
-        Ok(v) => v,
-        Err(e) => {
-            eprintln!("{}Error parsing schema: {}{}", RED, e, NC);
-            process::exit(1);
+        if report.omitted > 0 {
+            eprintln!("  (... and {} more)", report.omitted);
         }
-    };
+        if report.hidden > 0 {
+            eprintln!(
+                "  ({} more outside {}, hidden by --print-errors-only-for)",
+                report.hidden,
+                options.errors_only_for.as_deref().unwrap_or_default()
+            );
+        }
+        if report.errors.is_empty() && report.omitted == 0 && report.hidden == 0 {
+            eprintln!(
+                "  - {} warning(s), which fail under --strict",
+                report.warnings.len()
+            );
+        }
+        return exit(false);
+    }
 
-    // Read and parse .gitinfo file (with JSONC comment stripping)
-    let file_content = match fs::read_to_string(file_path) {
-        Ok(c) => c,
-        Err(e) => {
-            eprintln!("{}Error reading file: {}{}", RED, e, NC);
-            process::exit(1);
+    if !options.quiet {
+        println!("{}✓ {} is valid{}", GREEN, file_path, NC);
+    }
+    exit(true)
+}
+
+/// Whether data path `path` is `prefix` or below it: `.author` covers
+/// `.author.name` and `.author[0]` but not `.authors`.
+fn path_within(path: &str, prefix: &str) -> bool {
+    path.strip_prefix(prefix)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(['.', '[']) || prefix.is_empty())
+}
+
+/// Writes `line` to stdout as one line of `--format ndjson`, flushing so a
+/// reader sees it before the run ends.
+fn print_ndjson(line: &Value) {
+    let mut stdout = std::io::stdout().lock();
+    // A closed pipe just means nobody is reading any more
+    let _ = writeln!(stdout, "{}", line).and_then(|_| stdout.flush());
+}
+
+/// State carried across the files of one run.
+struct Session {
+    baseline: Baseline,
+    /// Errors reported so far, after baseline suppression.
+    error_count: usize,
+    /// Per-file outcomes, collected for `--format` reports.
+    results: Vec<FileResult>,
+    /// The `--github-repo` metadata, unless it couldn't be fetched.
+    github: Option<Value>,
+    /// Package names indexed for `--workspace`.
+    packages: Option<BTreeSet<String>>,
+    profile: Profile,
+}
+
+/// One file's outcome, as a `--format` report shows it.
+struct FileResult {
+    name: String,
+    valid: bool,
+    /// False when the file couldn't be read or parsed; the reason went to
+    /// stderr.
+    loaded: bool,
+    errors: Vec<ValidationError>,
+    warnings: Vec<String>,
+    /// Errors left out by `--max-errors`.
+    omitted: usize,
+    /// The file's JSON text, for locating errors by line.
+    json: String,
+}
+
+const HTML_STYLE: &str = "body{font-family:system-ui,sans-serif;margin:2rem;color:#222}\
+h2{font-size:1.1rem;margin-top:2rem}.valid{color:#1a7f37}.invalid{color:#cf222e}\
+table{border-collapse:collapse;width:100%}th,td{border:1px solid #d0d7de;padding:.3rem .6rem;text-align:left}\
+th{background:#f6f8fa}code{font-family:ui-monospace,monospace}.warning{color:#9a6700}.error{color:#cf222e}";
+
+/// Renders `results` as a self-contained HTML page: a section per file with
+/// a table of its errors and warnings.
+fn html_report(results: &[FileResult]) -> String {
+    let failed = results.iter().filter(|r| !r.valid).count();
+    let mut html = String::new();
+    html.push_str("<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n");
+    html.push_str("<title>gitinfo validation report</title>\n");
+    html.push_str(&format!("<style>{}</style>\n</head>\n<body>\n", HTML_STYLE));
+    html.push_str("<h1>gitinfo validation report</h1>\n");
+    html.push_str(&format!(
+        "<p>{} file(s) checked, {} failed</p>\n",
+        results.len(),
+        failed
+    ));
+    for result in results {
+        let (class, mark) = if result.valid {
+            ("valid", "✓")
+        } else {
+            ("invalid", "✗")
+        };
+        html.push_str(&format!(
+            "<h2 class=\"{}\">{} <code>{}</code></h2>\n",
+            class,
+            mark,
+            escape_html(&result.name)
+        ));
+        if !result.loaded {
+            html.push_str("<p>The file could not be read or parsed.</p>\n");
+            continue;
         }
-    };
+        if result.errors.is_empty() && result.warnings.is_empty() {
+            continue;
+        }
+        html.push_str("<table>\n<tr><th>Path</th><th>Message</th><th>Severity</th></tr>\n");
+        for error in &result.errors {
+            let message = match &error.suggestion {
+                Some(suggestion) => format!("{} (suggested: {})", error.message, suggestion),
+                None => error.message.clone(),
+            };
+            let message = match &error.source {
+                Some(source) => format!("[{}] {}", source, message),
+                None => message,
+            };
+            html_row(&mut html, display_path(&error.path), &message, "error");
+        }
+        for warning in &result.warnings {
+            // Warnings are already formatted as "path: message"
+            let (path, message) = warning.split_once(": ").unwrap_or(("", warning));
+            html_row(&mut html, path, message, "warning");
+        }
+        if result.omitted > 0 {
+            html.push_str(&format!(
+                "<tr><td colspan=\"3\">... and {} more</td></tr>\n",
+                result.omitted
+            ));
+        }
+        html.push_str("</table>\n");
+    }
+    html.push_str("</body>\n</html>\n");
+    html
+}
 
-    // Strip comments and trailing commas
-    let stripped = StripComments::new(file_content.as_bytes());
-    let mut json_str = String::new();
-    std::io::BufReader::new(stripped)
-        .read_to_string(&mut json_str)
-        .unwrap();
+/// Renders `results` as a Code Climate JSON array of issues, the format
+/// GitLab's code quality widget reads.
+fn codeclimate_report(results: &[FileResult]) -> String {
+    let mut issues = Vec::new();
+    for result in results {
+        if !result.loaded {
+            issues.push(codeclimate_issue(
+                result,
+                "parse",
+                "file could not be read or parsed".to_string(),
+                "",
+                "critical",
+                1,
+            ));
+        }
+        for error in &result.errors {
+            let path = error_location(error);
+            let line = json_line(&result.json, &path);
+            issues.push(codeclimate_issue(
+                result,
+                error.keyword,
+                error.to_string(),
+                &path,
+                "major",
+                line,
+            ));
+        }
+        for warning in &result.warnings {
+            let line = json_line(&result.json, warning_location(warning));
+            issues.push(codeclimate_issue(
+                result,
+                "warning",
+                warning.clone(),
+                warning,
+                "minor",
+                line,
+            ));
+        }
+    }
+    let mut report = serde_json::to_string_pretty(&issues).unwrap();
+    report.push('\n');
+    report
+}
 
-    // Remove trailing commas (JSONC allows them, JSON doesn't)
-    let trailing_comma_re = Regex::new(r",(\s*[}\]])").unwrap();
-    let json_str = trailing_comma_re.replace_all(&json_str, "$1");
+/// Renders `results` as GitLab annotations: a `path:line: message` line for
+/// each error and warning, which GitLab's job log turns into a link.
+fn gitlab_annotations(results: &[FileResult]) -> String {
+    let mut lines = String::new();
+    for result in results {
+        if !result.loaded {
+            lines.push_str(&format!(
+                "{}:1: file could not be read or parsed\n",
+                result.name
+            ));
+        }
+        for error in &result.errors {
+            let line = json_line(&result.json, &error_location(error));
+            lines.push_str(&format!("{}:{}: {}\n", result.name, line, error));
+        }
+        for warning in &result.warnings {
+            let line = json_line(&result.json, warning_location(warning));
+            lines.push_str(&format!("{}:{}: warning: {}\n", result.name, line, warning));
+        }
+        if result.omitted > 0 {
+            lines.push_str(&format!(
+                "{}: ... and {} more errors\n",
+                result.name, result.omitted
+            ));
+        }
+    }
+    lines
+}
 
-    let data: Value = match serde_json::from_str(&json_str) {
-        Ok(v) => v,
-        Err(e) => {
-            eprintln!("{}Error parsing JSONC: {}{}", RED, e, NC);
-            process::exit(1);
+/// The data path an error is located at: an unknown property at its key,
+/// not the object.
+fn error_location(error: &ValidationError) -> String {
+    match &error.property {
+        Some(property) => format!("{}.{}", error.path, property),
+        None => error.path.clone(),
+    }
+}
+
+/// The data path a warning is about, from its `path: message` text.
+fn warning_location(warning: &str) -> &str {
+    warning
+        .split_once(": ")
+        .map_or("", |(path, _)| path)
+        .trim_start_matches("root")
+}
+
+/// Renders `results` as JUnit XML: one test suite with a test case per
+/// file, failing with its errors.
+fn junit_report(results: &[FileResult]) -> String {
+    let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites>\n");
+    junit_suite(&mut xml, "gitinfo", results);
+    xml.push_str("</testsuites>\n");
+    xml
+}
+
+/// Writes a self-contained JUnit report for each of `results` into `dir`,
+/// named after the file's path with separators, other unsafe characters and
+/// a leading dot replaced by `_`, e.g. `packages_core_.gitinfo.xml`.
+fn write_split_junit(dir: &Path, results: &[FileResult]) -> std::io::Result<()> {
+    fs::create_dir_all(dir)?;
+    let mut used = BTreeSet::new();
+    for result in results {
+        let mut stem: String = result
+            .name
+            .trim_start_matches("./")
+            .chars()
+            .map(|c| {
+                if c.is_ascii_alphanumeric() || matches!(c, '.' | '-' | '_') {
+                    c
+                } else {
+                    '_'
+                }
+            })
+            .collect();
+        // A leading dot would hide the report from globs like *.xml
+        if stem.starts_with('.') {
+            stem.replace_range(..1, "_");
         }
-    };
+        // Distinct paths can sanitize to the same name
+        let mut name = format!("{}.xml", stem);
+        let mut n = 1;
+        while !used.insert(name.clone()) {
+            n += 1;
+            name = format!("{}-{}.xml", stem, n);
+        }
+        let mut xml = String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n");
+        junit_suite(&mut xml, &result.name, std::slice::from_ref(result));
+        fs::write(dir.join(name), xml)?;
+    }
+    Ok(())
+}
+
+/// Appends a `<testsuite>` named `name` with a test case per result.
+/// Errors go in the case's `<failure>`, warnings in its `<system-out>`, and
+/// a file that couldn't be read or parsed is an `<error>`.
+fn junit_suite(xml: &mut String, name: &str, results: &[FileResult]) {
+    let failures = results.iter().filter(|r| r.loaded && !r.valid).count();
+    let errors = results.iter().filter(|r| !r.loaded).count();
+    xml.push_str(&format!(
+        "<testsuite name=\"{}\" tests=\"{}\" failures=\"{}\" errors=\"{}\">\n",
+        escape_html(name),
+        results.len(),
+        failures,
+        errors
+    ));
+    for result in results {
+        xml.push_str(&format!(
+            "  <testcase classname=\"gitinfo\" name=\"{}\"",
+            escape_html(&result.name)
+        ));
+        if result.valid && result.warnings.is_empty() {
+            xml.push_str("/>\n");
+            continue;
+        }
+        xml.push_str(">\n");
+        if !result.loaded {
+            xml.push_str("    <error message=\"file could not be read or parsed\"/>\n");
+        } else if !result.valid {
+            let mut lines: Vec<String> = result.errors.iter().map(|e| e.to_string()).collect();
+            if result.omitted > 0 {
+                lines.push(format!("... and {} more", result.omitted));
+            }
+            if lines.is_empty() {
+                lines.push(format!(
+                    "{} warning(s), which fail under --strict",
+                    result.warnings.len()
+                ));
+            }
+            xml.push_str(&format!(
+                "    <failure message=\"{} error(s)\">{}</failure>\n",
+                result.errors.len() + result.omitted,
+                escape_html(&lines.join("\n"))
+            ));
+        }
+        if !result.warnings.is_empty() {
+            xml.push_str(&format!(
+                "    <system-out>{}</system-out>\n",
+                escape_html(&result.warnings.join("\n"))
+            ));
+        }
+        xml.push_str("  </testcase>\n");
+    }
+    xml.push_str("</testsuite>\n");
+}
 
-    // Validate
-    let errors = validate(&data, &schema);
+/// One Code Climate issue. The fingerprint hashes the file, data path and
+/// check, so GitLab can track an issue across pipelines.
+fn codeclimate_issue(
+    result: &FileResult,
+    check: &str,
+    description: String,
+    path: &str,
+    severity: &str,
+    line: usize,
+) -> Value {
+    let fingerprint = fnv1a(&format!("{}\0{}\0{}", result.name, path, check));
+    serde_json::json!({
+        "type": "issue",
+        "check_name": check,
+        "description": description,
+        "fingerprint": format!("{:016x}", fingerprint),
+        "severity": severity,
+        "location": {
+            "path": result.name,
+            "lines": { "begin": line },
+        },
+    })
+}
 
-    if !errors.is_empty() {
-        eprintln!("{}Validation failed for {}:{}", RED, file_path, NC);
-        for error in &errors {
-            eprintln!("  - {}", error);
+/// 64-bit FNV-1a: a small hash that, unlike `DefaultHasher`, is stable
+/// across Rust releases.
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+/// The 1-based line of the value at data `path` in `json`, or line 1 when
+/// it can't be found.
+fn json_line(json: &str, path: &str) -> usize {
+    let offset = value_offset(json, path).unwrap_or(0);
+    json[..offset].matches('\n').count() + 1
+}
+
+/// Byte offset of the value at data `path` in `json`, found by scanning the
+/// text, since parsed values don't keep their positions.
+fn value_offset(json: &str, path: &str) -> Option<usize> {
+    let mut offset = skip_whitespace(json, 0);
+    for segment in path_segments(path) {
+        offset = child_offset(json, offset, &segment)?;
+    }
+    Some(offset)
+}
+
+/// Offset of the member or element `segment` within the object or array
+/// starting at `start`.
+fn child_offset(json: &str, start: usize, segment: &PathSegment) -> Option<usize> {
+    let bytes = json.as_bytes();
+    let (close, key) = match (bytes.get(start)?, segment) {
+        (b'{', PathSegment::Key(key)) => (b'}', Some(key)),
+        (b'[', PathSegment::Index(_)) => (b']', None),
+        _ => return None,
+    };
+    let mut pos = start + 1;
+    let mut index = 0;
+    loop {
+        pos = skip_whitespace(json, pos);
+        if *bytes.get(pos)? == close {
+            return None;
+        }
+        let found = if let Some(key) = key {
+            let mut names = serde_json::Deserializer::from_str(&json[pos..]).into_iter::<String>();
+            let name = names.next()?.ok()?;
+            pos = skip_whitespace(json, pos + names.byte_offset());
+            if *bytes.get(pos)? != b':' {
+                return None;
+            }
+            pos = skip_whitespace(json, pos + 1);
+            name == *key
+        } else {
+            *segment == PathSegment::Index(index)
+        };
+        if found {
+            return Some(pos);
         }
-        process::exit(1);
+        let mut values =
+            serde_json::Deserializer::from_str(&json[pos..]).into_iter::<serde::de::IgnoredAny>();
+        values.next()?.ok()?;
+        pos = skip_whitespace(json, pos + values.byte_offset());
+        if *bytes.get(pos)? == b',' {
+            pos += 1;
+        }
+        index += 1;
     }
+}
 
-    println!("{}✓ {} is valid{}", GREEN, file_path, NC);
+fn skip_whitespace(json: &str, pos: usize) -> usize {
+    let rest = &json[pos..];
+    pos + rest.len() - rest.trim_start().len()
 }
 
-fn validate(data: &Value, schema: &Value) -> Vec<String> {
-    let mut errors = Vec::new();
+fn html_row(html: &mut String, path: &str, message: &str, severity: &str) {
+    html.push_str(&format!(
+        "<tr><td><code>{}</code></td><td>{}</td><td class=\"{}\">{}</td></tr>\n",
+        escape_html(path),
+        escape_html(message),
+        severity,
+        severity
+    ));
+}
 
-    // Check if root is an object
-    if !data.is_object() {
-        errors.push("root: expected object".to_string());
-        return errors;
+/// Escapes text for use in HTML content and attribute values.
+fn escape_html(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
     }
+    escaped
+}
+
+/// How many of the slowest files `--profile` lists.
+const SLOWEST_FILES: usize = 5;
 
-    let data_obj = data.as_object().unwrap();
-    let properties = schema
-        .get("properties")
-        .and_then(|p| p.as_object())
-        .unwrap();
+/// Time spent in each phase, summed over all files, for `--profile`.
+#[derive(Default)]
+struct Profile {
+    schema: Duration,
+    read: Duration,
+    strip: Duration,
+    parse: Duration,
+    validate: Duration,
+    files: usize,
+    /// Each file checked and the time it took, from reading to reporting.
+    per_file: Vec<(String, Duration)>,
+}
 
-    // Check additionalProperties
-    if schema.get("additionalProperties") == Some(&Value::Bool(false)) {
-        let allowed: HashSet<&str> = properties.keys().map(|k| k.as_str()).collect();
-        for key in data_obj.keys() {
-            if !allowed.contains(key.as_str()) {
-                errors.push(format!("root: unknown property \"{}\"", key));
+impl Profile {
+    fn print(&self, total: Duration) {
+        eprintln!("Profile ({} file(s)):", self.files);
+        let phases = [
+            ("schema", self.schema),
+            ("read", self.read),
+            ("strip", self.strip),
+            ("parse", self.parse),
+            ("validate", self.validate),
+            ("total", total),
+        ];
+        for (phase, duration) in phases {
+            eprintln!("  {:<9}{:>10.3} ms", phase, duration.as_secs_f64() * 1000.0);
+        }
+        if self.per_file.len() > 1 {
+            let mut slowest: Vec<&(String, Duration)> = self.per_file.iter().collect();
+            slowest.sort_by_key(|(_, duration)| std::cmp::Reverse(*duration));
+            slowest.truncate(SLOWEST_FILES);
+            eprintln!("Slowest {} file(s):", slowest.len());
+            for (name, duration) in slowest {
+                eprintln!("  {:>10.3} ms  {}", duration.as_secs_f64() * 1000.0, name);
             }
         }
     }
+}
+
+/// Known errors to suppress, read from `--baseline`, and the errors seen
+/// this run, for `--write-baseline`. Entries match on file, data path and
+/// keyword; the message is kept only for readers of the baseline file.
+/// Each entry suppresses one error, so a second unknown property at the
+/// same path is still reported.
+#[derive(Default)]
+struct Baseline {
+    known: HashMap<(String, String, String), usize>,
+    recording: bool,
+    recorded: Vec<Value>,
+}
+
+impl Baseline {
+    fn load(path: &Path) -> Result<Self, String> {
+        let content = fs::read_to_string(path).map_err(|e| e.to_string())?;
+        let entries: Vec<Value> = serde_json::from_str(&content).map_err(|e| e.to_string())?;
+        let mut known = HashMap::new();
+        for entry in &entries {
+            let field = |name: &str| {
+                entry
+                    .get(name)
+                    .and_then(|v| v.as_str())
+                    .map(str::to_string)
+                    .ok_or_else(|| format!("baseline entry missing \"{}\": {}", name, entry))
+            };
+            let key = (field("file")?, field("path")?, field("keyword")?);
+            *known.entry(key).or_insert(0) += 1;
+        }
+        Ok(Baseline {
+            known,
+            ..Baseline::default()
+        })
+    }
+
+    /// Drops known errors from `errors`, returning how many were dropped.
+    fn suppress(&mut self, file: &str, errors: &mut Vec<ValidationError>) -> usize {
+        let before = errors.len();
+        errors.retain(|e| {
+            let key = (file.to_string(), e.path.clone(), e.keyword.to_string());
+            match self.known.get_mut(&key) {
+                Some(count) if *count > 0 => {
+                    *count -= 1;
+                    false
+                }
+                _ => true,
+            }
+        });
+        before - errors.len()
+    }
 
-    // Validate each property
-    for (key, prop_schema) in properties {
-        if let Some(value) = data_obj.get(key) {
-            validate_property(&mut errors, &format!(".{}", key), value, prop_schema);
+    fn record(&mut self, file: &str, errors: &[ValidationError]) {
+        for error in errors {
+            self.recorded.push(serde_json::json!({
+                "file": file,
+                "path": error.path,
+                "keyword": error.keyword,
+                "message": error.message,
+            }));
         }
     }
 
-    errors
+    fn write(&self, path: &Path) -> Result<(), String> {
+        let mut json = serde_json::to_string_pretty(&self.recorded).map_err(|e| e.to_string())?;
+        json.push('\n');
+        fs::write(path, json).map_err(|e| e.to_string())
+    }
+}
+
+/// How often one field is set across the files `stats` read.
+struct FieldStats {
+    field: String,
+    /// Files where the field is present and not null or blank.
+    present: usize,
+    /// Occurrences of each scalar value, counting array items separately.
+    values: BTreeMap<String, usize>,
+}
+
+impl FieldStats {
+    fn add(&mut self, value: Option<&Value>) {
+        let blank = match value {
+            None | Some(Value::Null) => true,
+            Some(Value::String(s)) => s.trim().is_empty(),
+            Some(_) => false,
+        };
+        if blank {
+            return;
+        }
+        self.present += 1;
+        let items = match value {
+            Some(Value::Array(items)) => items.iter().collect(),
+            other => other.into_iter().collect::<Vec<_>>(),
+        };
+        for item in items {
+            let key = match item {
+                Value::String(s) => s.clone(),
+                Value::Number(_) | Value::Bool(_) => item.to_string(),
+                _ => continue,
+            };
+            *self.values.entry(key).or_default() += 1;
+        }
+    }
 }
 
-fn validate_property(errors: &mut Vec<String>, path: &str, value: &Value, schema: &Value) {
-    let expected_type = schema.get("type").and_then(|t| t.as_str());
+/// Parses every `.gitinfo` file named or found under the named directories
+/// and prints how often each `--fields` entry is set, with its most common
+/// values. Returns the exit code: only unreadable files make it non-zero.
+fn print_stats(options: &Options) -> i32 {
+    let files = expand_dirs(&options.files);
+    let fields: Vec<String> = if options.fields.is_empty() {
+        DEFAULT_STATS_FIELDS.iter().map(|f| f.to_string()).collect()
+    } else {
+        options.fields.clone()
+    };
+    let mut stats: Vec<FieldStats> = fields
+        .into_iter()
+        .map(|field| FieldStats {
+            field,
+            present: 0,
+            values: BTreeMap::new(),
+        })
+        .collect();
 
-    match expected_type {
-        Some("string") => {
-            if !value.is_string() {
-                errors.push(format!("{}: expected string", path));
-                return;
+    let mut exit_code = EXIT_VALID;
+    let mut parsed = 0;
+    for file_path in &files {
+        let data = match load_file(file_path, options, &mut Profile::default()) {
+            Ok((_, data, _, _)) => data,
+            Err(code) => {
+                exit_code = exit_code.max(code);
+                continue;
             }
-            let s = value.as_str().unwrap();
+        };
+        parsed += 1;
+        for field in &mut stats {
+            field.add(value_at_path(&data, &format!(".{}", field.field)));
+        }
+    }
 
-            // Check format
-            if let Some(format) = schema.get("format").and_then(|f| f.as_str()) {
-                match format {
-                    "uri" => {
-                        if !is_valid_uri(s) {
-                            errors.push(format!("{}: invalid URI \"{}\"", path, s));
-                        }
-                    }
-                    "email" => {
-                        if !is_valid_email(s) {
-                            errors.push(format!("{}: invalid email \"{}\"", path, s));
-                        }
+    let unreadable = files.len() - parsed;
+    if unreadable > 0 {
+        println!("{} file(s), {} unreadable", files.len(), unreadable);
+    } else {
+        println!("{} file(s)", files.len());
+    }
+    for field in &stats {
+        println!(
+            "{}: set in {} of {} ({} missing)",
+            field.field,
+            field.present,
+            parsed,
+            parsed - field.present
+        );
+        // Values that never repeat (descriptions, URLs) say nothing in bulk
+        if field.values.values().all(|&count| count < 2) {
+            continue;
+        }
+        let mut values: Vec<(&String, &usize)> = field.values.iter().collect();
+        values.sort_by(|a, b| b.1.cmp(a.1));
+        for (value, count) in values.iter().take(STATS_TOP_VALUES) {
+            println!("  {:>5}  {}", count, value);
+        }
+        if values.len() > STATS_TOP_VALUES {
+            println!("  ... and {} more", values.len() - STATS_TOP_VALUES);
+        }
+    }
+    exit_code
+}
+
+/// Validates two files, then prints a field-by-field diff between them.
+/// Returns the most severe exit code of the two validations.
+fn diff_files(
+    a: &str,
+    b: &str,
+    schemas: &SchemaSet,
+    options: &Options,
+    session: &mut Session,
+) -> i32 {
+    let (a_name, a_data, a_json, _) = match load_file(a, options, &mut session.profile) {
+        Ok(loaded) => loaded,
+        Err(code) => return code,
+    };
+    let (b_name, b_data, b_json, _) = match load_file(b, options, &mut session.profile) {
+        Ok(loaded) => loaded,
+        Err(code) => return code,
+    };
+
+    let phase = Instant::now();
+    let (base, dir) = (&schemas.base.schema, &schemas.base.dir);
+    let a_report = Report::new(&a_data, base, dir, options);
+    let a_report = apply_overlays(a_report, &a_data, schemas, options);
+    let b_report = Report::new(&b_data, base, dir, options);
+    let b_report = apply_overlays(b_report, &b_data, schemas, options);
+    session.profile.validate += phase.elapsed();
+    let exit_code = print_report(a_name, &a_data, &a_json, a_report, options, session).max(
+        print_report(b_name, &b_data, &b_json, b_report, options, session),
+    );
+
+    println!("Diff {} -> {}:", a_name, b_name);
+    if print_diff("", &a_data, &b_data) == 0 {
+        println!("  No differences");
+    }
+    exit_code
+}
+
+/// Prints additions, removals and changes between `old` and `new`,
+/// recursing into objects and arrays. Returns the number of differences.
+fn print_diff(path: &str, old: &Value, new: &Value) -> usize {
+    match (old, new) {
+        (Value::Object(old_obj), Value::Object(new_obj)) => {
+            let keys: BTreeSet<&String> = old_obj.keys().chain(new_obj.keys()).collect();
+            keys.into_iter()
+                .map(|key| {
+                    let child = format!("{}.{}", path, key);
+                    print_diff_entry(&child, old_obj.get(key), new_obj.get(key))
+                })
+                .sum()
+        }
+        (Value::Array(old_arr), Value::Array(new_arr)) => (0..old_arr.len().max(new_arr.len()))
+            .map(|i| {
+                let child = format!("{}[{}]", path, i);
+                print_diff_entry(&child, old_arr.get(i), new_arr.get(i))
+            })
+            .sum(),
+        _ if old == new => 0,
+        _ => {
+            let path = if path.is_empty() { "root" } else { path };
+            println!("{}  ~ {}: {} -> {}{}", YELLOW, path, old, new, NC);
+            1
+        }
+    }
+}
+
+fn print_diff_entry(path: &str, old: Option<&Value>, new: Option<&Value>) -> usize {
+    match (old, new) {
+        (Some(old), Some(new)) => print_diff(path, old, new),
+        (Some(old), None) => {
+            println!("{}  - {}: {}{}", RED, path, old, NC);
+            1
+        }
+        (None, Some(new)) => {
+            println!("{}  + {}: {}{}", GREEN, path, new, NC);
+            1
+        }
+        (None, None) => 0,
+    }
+}
+
+/// Prints a drill-down for the errors at `pointer`: why each failed, the
+/// actual value and the subschema it failed against.
+fn explain_error(data: &Value, pointer: &str, errors: &[ValidationError]) {
+    let Some((path, value)) = resolve_pointer(data, pointer) else {
+        println!("No value at {}", pointer);
+        return;
+    };
+    let matching: Vec<&ValidationError> = errors.iter().filter(|e| e.path == path).collect();
+    if matching.is_empty() {
+        println!("No error at {} ({})", pointer, display_path(&path));
+        return;
+    }
+
+    for error in matching {
+        println!(
+            "{}Error at {} [{}]:{}",
+            RED,
+            display_path(&path),
+            error.keyword,
+            NC
+        );
+        println!("  {}", error.message);
+        println!("Why:");
+        println!("  {}", explain_keyword(error.keyword));
+        println!("Value:");
+        print_indented(value);
+        if !error.schema.is_null() {
+            println!("Schema:");
+            print_indented(&error.schema);
+        }
+    }
+}
+
+/// Renders `schema` as Markdown for `--explain-schema`: the title and
+/// description, then a table of the root's properties and one for each
+/// nested object, reached through properties, array items and local `$ref`s.
+/// An object met again, say through a recursive `$ref`, links to its table.
+fn schema_docs(schema: &Value) -> String {
+    let mut doc = String::new();
+    let title = schema
+        .get("title")
+        .and_then(Value::as_str)
+        .unwrap_or("Schema");
+    doc.push_str(&format!("# {}\n", title));
+    if let Some(description) = schema.get("description").and_then(Value::as_str) {
+        doc.push_str(&format!("\n{}\n", description));
+    }
+    let mut pending = vec![(String::new(), schema)];
+    // Each documented object's subschema and the path of its table
+    let mut documented: Vec<(&Value, String)> = vec![(schema, String::new())];
+    while !pending.is_empty() {
+        let (path, node) = pending.remove(0);
+        let Some(properties) = node.get("properties").and_then(Value::as_object) else {
+            continue;
+        };
+        let required: Vec<&str> = node
+            .get("required")
+            .and_then(Value::as_array)
+            .map(|r| r.iter().filter_map(Value::as_str).collect())
+            .unwrap_or_default();
+        if path.is_empty() {
+            doc.push_str("\n## Properties\n\n");
+        } else {
+            doc.push_str(&format!("\n## `{}`\n\n", path));
+        }
+        if node.get("additionalProperties") == Some(&Value::Bool(false)) {
+            doc.push_str("No other properties are allowed.\n\n");
+        }
+        doc.push_str("| Property | Type | Required | Description | Constraints |\n");
+        doc.push_str("|----------|------|----------|-------------|-------------|\n");
+        for (key, property) in properties {
+            let property = resolve_local_ref(schema, property);
+            // Nested objects, directly or as array items, get their own table
+            let mut child = (format!("{}.{}", path, key), property);
+            while let Some(items) = child.1.get("items").filter(|i| i.is_object()) {
+                child = (format!("{}[]", child.0), resolve_local_ref(schema, items));
+            }
+            let mut type_cell = type_name(schema, property);
+            if child.1.get("properties").is_some_and(Value::is_object) {
+                match documented
+                    .iter()
+                    .find(|(node, _)| std::ptr::eq(*node, child.1))
+                {
+                    Some((_, table)) if table.is_empty() => type_cell.push_str(" (see Properties)"),
+                    Some((_, table)) => type_cell.push_str(&format!(" (see `{}`)", table)),
+                    None => {
+                        documented.push((child.1, child.0.clone()));
+                        pending.push(child);
                     }
-                    _ => {}
                 }
             }
+            let description = property
+                .get("description")
+                .and_then(Value::as_str)
+                .unwrap_or_default();
+            doc.push_str(&format!(
+                "| `{}` | {} | {} | {} | {} |\n",
+                key,
+                table_cell(&type_cell),
+                if required.contains(&key.as_str()) {
+                    "yes"
+                } else {
+                    "no"
+                },
+                table_cell(description),
+                table_cell(&constraints(schema, property))
+            ));
+        }
+    }
+    doc
+}
 
-            // Check pattern
-            if let Some(pattern) = schema.get("pattern").and_then(|p| p.as_str()) {
-                if let Ok(re) = Regex::new(pattern) {
-                    if !re.is_match(s) {
-                        errors.push(format!("{}: does not match pattern {}", path, pattern));
-                    }
-                }
+/// Writes a `.gitinfo` template for `schema` to `path`, or stdout for `-`,
+/// refusing to overwrite a file. Returns the exit code.
+fn write_template(path: &str, schema: &Value) -> i32 {
+    let template = init_template(schema);
+    if path == "-" {
+        print!("{}", template);
+        return EXIT_VALID;
+    }
+    if Path::new(path).exists() {
+        eprintln!("{}Error: {} already exists{}", RED, path, NC);
+        return EXIT_ERROR;
+    }
+    if let Err(e) = fs::write(path, template) {
+        eprintln!("{}Error writing {}: {}{}", RED, path, e, NC);
+        return EXIT_ERROR;
+    }
+    println!("Wrote {}", path);
+    EXIT_VALID
+}
+
+/// A JSONC template with every property of `schema`, each preceded by its
+/// `description` as a comment. Nested objects are filled in the same way.
+fn init_template(schema: &Value) -> String {
+    let mut template = String::new();
+    init_object(&mut template, schema, schema, 0);
+    template.push('\n');
+    template
+}
+
+fn init_object(out: &mut String, root: &Value, schema: &Value, depth: usize) {
+    let schema = resolve_local_ref(root, schema);
+    let properties = match schema.get("properties").and_then(Value::as_object) {
+        Some(properties) if !properties.is_empty() => properties,
+        _ => {
+            out.push_str("{}");
+            return;
+        }
+    };
+    let indent = "  ".repeat(depth + 1);
+    out.push_str("{\n");
+    for (i, (key, property)) in properties.iter().enumerate() {
+        let property = resolve_local_ref(root, property);
+        if let Some(description) = property.get("description").and_then(Value::as_str) {
+            for line in description.lines() {
+                out.push_str(&format!("{}// {}\n", indent, line));
             }
+        }
+        out.push_str(&format!("{}{}: ", indent, Value::from(key.as_str())));
+        match placeholder(root, key, property) {
+            Some(value) => out.push_str(&value.to_string()),
+            None => init_object(out, root, property, depth + 1),
+        }
+        out.push_str(if i + 1 < properties.len() {
+            ",\n"
+        } else {
+            "\n"
+        });
+    }
+    out.push_str(&"  ".repeat(depth));
+    out.push('}');
+}
+
+/// The value a template gives a property: its first `examples` entry, else
+/// its `default`, `const` or first `enum` value, else an empty value of its
+/// type. `$schema` defaults to the schema's `$id`. `None` means an object
+/// to fill in property by property.
+fn placeholder(root: &Value, key: &str, schema: &Value) -> Option<Value> {
+    let first_example = schema
+        .get("examples")
+        .and_then(Value::as_array)
+        .and_then(|examples| examples.first());
+    let given = first_example
+        .or(schema.get("default"))
+        .or(schema.get("const"))
+        .or(schema
+            .get("enum")
+            .and_then(Value::as_array)
+            .and_then(|members| members.first()));
+    if let Some(given) = given {
+        return Some(given.clone());
+    }
+    if key == "$schema" {
+        if let Some(id) = root.get("$id") {
+            return Some(id.clone());
+        }
+    }
+    let declared = match schema.get("type") {
+        Some(Value::Array(types)) => types.first().and_then(Value::as_str),
+        other => other.and_then(Value::as_str),
+    };
+    match declared {
+        Some("object") => None,
+        Some("array") => Some(Value::Array(Vec::new())),
+        Some("integer" | "number") => Some(Value::from(0)),
+        Some("boolean") => Some(Value::Bool(false)),
+        Some("null") => Some(Value::Null),
+        _ if schema.get("properties").is_some() => None,
+        _ => Some(Value::from("")),
+    }
+}
+
+/// Follows a local `#/...` `$ref` to its target; anything else is returned
+/// as is.
+fn resolve_local_ref<'a>(root: &'a Value, schema: &'a Value) -> &'a Value {
+    let mut current = schema;
+    // A bounded walk, so a ref cycle can't loop forever
+    for _ in 0..16 {
+        let Some(pointer) = current
+            .get("$ref")
+            .and_then(Value::as_str)
+            .and_then(|r| r.strip_prefix('#'))
+        else {
+            break;
+        };
+        match root.pointer(pointer) {
+            Some(target) => current = target,
+            None => break,
+        }
+    }
+    current
+}
 
-            // Check minLength
-            if let Some(min_len) = schema.get("minLength").and_then(|m| m.as_u64()) {
-                if (s.len() as u64) < min_len {
-                    errors.push(format!("{}: string too short (min {})", path, min_len));
+/// A short description of what `schema` accepts, like `string`,
+/// `array of string` or `[string, string]`.
+fn type_name(root: &Value, schema: &Value) -> String {
+    let schema = resolve_local_ref(root, schema);
+    let tuple = schema
+        .get("prefixItems")
+        .or(schema.get("items"))
+        .and_then(Value::as_array);
+    if let Some(tuple) = tuple {
+        let members: Vec<String> = tuple.iter().map(|item| type_name(root, item)).collect();
+        return format!("[{}]", members.join(", "));
+    }
+    let declared = match schema.get("type") {
+        Some(Value::String(t)) => Some(t.clone()),
+        Some(Value::Array(types)) => Some(
+            types
+                .iter()
+                .filter_map(Value::as_str)
+                .collect::<Vec<_>>()
+                .join(" or "),
+        ),
+        _ => None,
+    };
+    match declared.as_deref() {
+        Some("array") => match schema.get("items") {
+            Some(items) => format!("array of {}", type_name(root, items)),
+            None => "array".to_string(),
+        },
+        Some(t) => t.to_string(),
+        None => {
+            let branches = schema.get("oneOf").or(schema.get("anyOf"));
+            match branches.and_then(Value::as_array) {
+                Some(branches) => {
+                    let names: Vec<String> = branches.iter().map(|b| type_name(root, b)).collect();
+                    names.join(" or ")
                 }
+                None if schema.get("enum").is_some() => "enum".to_string(),
+                None => "any".to_string(),
             }
         }
-        Some("array") => {
-            if !value.is_array() {
-                errors.push(format!("{}: expected array", path));
-                return;
-            }
-            let arr = value.as_array().unwrap();
+    }
+}
 
-            // Validate items
-            if let Some(items_schema) = schema.get("items") {
-                if items_schema.is_array() {
-                    // Tuple validation
-                    let items_schemas = items_schema.as_array().unwrap();
-                    for (i, item) in arr.iter().enumerate() {
-                        if let Some(item_schema) = items_schemas.get(i) {
-                            validate_property(errors, &format!("{}[{}]", path, i), item, item_schema);
-                        }
-                    }
-                    // Check minItems/maxItems
-                    if let Some(min) = schema.get("minItems").and_then(|m| m.as_u64()) {
-                        if (arr.len() as u64) < min {
-                            errors.push(format!("{}: expected at least {} items", path, min));
-                        }
-                    }
-                    if let Some(max) = schema.get("maxItems").and_then(|m| m.as_u64()) {
-                        if (arr.len() as u64) > max {
-                            errors.push(format!("{}: expected at most {} items", path, max));
-                        }
-                    }
-                } else {
-                    // Array of same type
-                    for (i, item) in arr.iter().enumerate() {
-                        validate_property(errors, &format!("{}[{}]", path, i), item, items_schema);
-                    }
+/// The checks `schema` applies beyond its type, separated by `; `.
+fn constraints(root: &Value, schema: &Value) -> String {
+    let mut parts = Vec::new();
+    if let Some(format) = schema.get("format").and_then(Value::as_str) {
+        parts.push(format!("format `{}`", format));
+    }
+    match (
+        schema.get("pattern").and_then(Value::as_str),
+        schema.get("x-patternDescription").and_then(Value::as_str),
+    ) {
+        (Some(_), Some(description)) => parts.push(format!("must be {}", description)),
+        (Some(pattern), None) => parts.push(format!("matches `{}`", pattern)),
+        _ => {}
+    }
+    if let Some(examples) = schema.get("x-patternExamples").and_then(Value::as_array) {
+        let examples: Vec<String> = examples
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|e| format!("`{}`", e))
+            .collect();
+        if !examples.is_empty() {
+            parts.push(format!("e.g. {}", examples.join(", ")));
+        }
+    }
+    if let Some(members) = schema.get("enum").and_then(Value::as_array) {
+        let descriptions = schema.get("x-enumDescriptions");
+        let values: Vec<String> = members
+            .iter()
+            .map(|member| {
+                let name = member.as_str().map_or(member.to_string(), str::to_string);
+                match descriptions
+                    .and_then(|d| d.get(&name))
+                    .and_then(Value::as_str)
+                {
+                    Some(description) => format!("`{}` ({})", name, description),
+                    None => format!("`{}`", name),
                 }
-            }
+            })
+            .collect();
+        parts.push(format!("one of {}", values.join(", ")));
+    }
+    if let Some(value) = schema.get("const") {
+        parts.push(format!("always `{}`", value));
+    }
+    let bounds = [
+        ("minLength", "at least", " character"),
+        ("maxLength", "at most", " character"),
+        ("minItems", "at least", " item"),
+        ("maxItems", "at most", " item"),
+        ("minimum", "at least", ""),
+        ("exclusiveMinimum", "greater than", ""),
+        ("maximum", "at most", ""),
+        ("exclusiveMaximum", "less than", ""),
+    ];
+    for (keyword, limit, unit) in bounds {
+        if let Some(bound) = schema.get(keyword).filter(|b| b.is_number()) {
+            let plural = if unit.is_empty() || bound == 1 {
+                ""
+            } else {
+                "s"
+            };
+            parts.push(format!("{} {}{}{}", limit, bound, unit, plural));
+        }
+    }
+    if let Some(default) = schema.get("default") {
+        parts.push(format!("default `{}`", default));
+    }
+    if schema.get("deprecated") == Some(&Value::Bool(true)) {
+        match schema
+            .get("x-deprecatedReplacement")
+            .and_then(Value::as_str)
+        {
+            Some(replacement) => parts.push(format!("deprecated, use `{}` instead", replacement)),
+            None => parts.push("deprecated".to_string()),
+        }
+    }
+    if let Some(aliases) = schema.get("x-aliases").and_then(Value::as_array) {
+        let names: Vec<String> = aliases
+            .iter()
+            .filter_map(Value::as_str)
+            .map(|a| format!("`{}`", a))
+            .collect();
+        if !names.is_empty() {
+            parts.push(format!("formerly {}", names.join(", ")));
+        }
+    }
+    // A same-typed array's item checks, unless the items get their own table
+    if let Some(items) = schema.get("items").filter(|i| i.is_object()) {
+        let items = resolve_local_ref(root, items);
+        let item_constraints = constraints(root, items);
+        if !item_constraints.is_empty() && items.get("properties").is_none() {
+            parts.push(format!("items: {}", item_constraints));
+        }
+    }
+    parts.join("; ")
+}
+
+/// Makes `text` safe inside a Markdown table cell.
+fn table_cell(text: &str) -> String {
+    text.replace('|', "\\|").replace('\n', " ")
+}
+
+/// Human explanation of what a failing keyword requires.
+fn explain_keyword(keyword: &str) -> String {
+    match keyword {
+        "type" => "The value has a different JSON type than the schema's `type` requires.".to_string(),
+        "format" => "The string doesn't follow the syntax required by the schema's `format`.".to_string(),
+        "pattern" => "The string doesn't match the regular expression in `pattern`.".to_string(),
+        "enum" => "The value isn't one of the values listed in `enum`; with `--verbose`, the error describes each allowed value from `x-enumDescriptions`.".to_string(),
+        "const" => "The value differs from the one required by `const`.".to_string(),
+        "oneOf" => "The value must match exactly one `oneOf` branch, but matches none or several.".to_string(),
+        "anyOf" => "The value must match at least one `anyOf` branch, but matches none.".to_string(),
+        "x-discriminator" => "The discriminator field is missing, or no `oneOf` branch declares its value as `const`.".to_string(),
+        "minLength" => "The string is shorter than `minLength` allows.".to_string(),
+        "maxLength" => "The string is longer than `maxLength` allows.".to_string(),
+        "propertyNames" => "A property name fails the string schema in `propertyNames`.".to_string(),
+        "minItems" => "The array has fewer items than `minItems` requires.".to_string(),
+        "maxItems" => "The array has more items than `maxItems` allows.".to_string(),
+        "items" => "The array has more items than the tuple defines, and extra items are not allowed.".to_string(),
+        "minimum" => "The number is smaller than `minimum` allows.".to_string(),
+        "maximum" => "The number is larger than `maximum` allows.".to_string(),
+        "exclusiveMinimum" => "The number isn't greater than `exclusiveMinimum`.".to_string(),
+        "exclusiveMaximum" => "The number isn't less than `exclusiveMaximum`.".to_string(),
+        "dependencies" | "dependentRequired" => "A property that's set requires others, listed under its name in `dependencies` (draft-07) or `dependentRequired` (2019-09 and later), that are missing.".to_string(),
+        "additionalProperties" => "The object has a property the schema doesn't define, and `additionalProperties` is false.".to_string(),
+        "unevaluatedProperties" => "No `properties`, `patternProperties` or applicable combinator branch covers this property, and `unevaluatedProperties` is false.".to_string(),
+        "unevaluatedItems" => "No tuple or `items` schema covers this array item, and `unevaluatedItems` is false.".to_string(),
+        "x-allowedMediaTypes" => "The data URI's image type isn't listed in `x-allowedMediaTypes`.".to_string(),
+        "x-fundingPlatform" => "The URL isn't on the host of the funding platform named by `x-fundingPlatform`.".to_string(),
+        "x-githubTopics" => "GitHub rejects more than 20 topics on a repository, and topics longer than 50 characters.".to_string(),
+        "x-oneOfFields" => "The schema's `x-oneOfFields` lists fields that are alternatives: exactly one of them must be set.".to_string(),
+        "x-aliases" => "The object sets a property under both its current name and an old name from `x-aliases`; remove the old one.".to_string(),
+        "x-requiredIf" => "The object's `x-requiredIf` rule requires the listed fields once its `field` equals the given value.".to_string(),
+        "x-sortedBy" => "The array's items must be ordered by the field named in `x-sortedBy`, ascending unless its `order` is `\"desc\"`; the index is the first item out of place.".to_string(),
+        "x-localPath" => "The field is a path relative to the file, and with `--check-files` it must name an existing file; for `\"image\"` a PNG, JPEG, WebP or SVG whose contents match its extension.".to_string(),
+        "maxDepth" => "Subschemas nest more deeply than `--max-depth` allows (64 by default), usually because of a `$ref` that keeps descending; validation stopped there.".to_string(),
+        "$ref" => "The schema's `$ref` could not be followed, so the value couldn't be checked.".to_string(),
+        other => format!("The value violates the schema's `{}` keyword.", other),
+    }
+}
+
+fn print_indented(value: &Value) {
+    let pretty = serde_json::to_string_pretty(value).unwrap_or_default();
+    for line in pretty.lines() {
+        println!("  {}", line);
+    }
+}
+
+/// Rebuilds `value` with every object's keys in sorted order. serde_json's
+/// `Map` already iterates in key order unless its `preserve_order` feature
+/// is enabled; sorting explicitly keeps `--sort-keys` stable either way.
+fn sort_keys(value: Value) -> Value {
+    match value {
+        Value::Object(obj) => {
+            let mut entries: Vec<(String, Value)> = obj.into_iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(&b.0));
+            Value::Object(
+                entries
+                    .into_iter()
+                    .map(|(k, v)| (k, sort_keys(v)))
+                    .collect(),
+            )
         }
-        Some("object") => {
-            if !value.is_object() {
-                errors.push(format!("{}: expected object", path));
+        Value::Array(arr) => Value::Array(arr.into_iter().map(sort_keys).collect()),
+        other => other,
+    }
+}
+
+/// One step of a data path: an object key or an array index.
+#[derive(PartialEq, Eq, PartialOrd, Ord)]
+enum PathSegment {
+    Index(usize),
+    Key(String),
+}
+
+/// Splits a data path like `.maintainers[10][1]` into segments, so paths
+/// sort with the root first and array indices in numeric order.
+fn path_segments(path: &str) -> Vec<PathSegment> {
+    let mut segments = Vec::new();
+    let mut rest = path;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix('[') {
+            let end = after.find(']').unwrap_or(after.len());
+            match after[..end].parse() {
+                Ok(index) => segments.push(PathSegment::Index(index)),
+                Err(_) => segments.push(PathSegment::Key(after[..end].to_string())),
             }
+            rest = after.get(end + 1..).unwrap_or("");
+        } else {
+            let after = rest.strip_prefix('.').unwrap_or(rest);
+            let end = after.find(['.', '[']).unwrap_or(after.len());
+            segments.push(PathSegment::Key(after[..end].to_string()));
+            rest = &after[end..];
         }
-        _ => {}
     }
+    segments
 }
 
-fn is_valid_uri(s: &str) -> bool {
-    s.starts_with("http://") || s.starts_with("https://") || s.starts_with("data:image/")
+/// Replaces curly double and single quotes with their ASCII equivalents,
+/// returning the new text and the number of substitutions.
+fn normalize_quotes(content: &str) -> (String, usize) {
+    let mut count = 0;
+    let normalized = content
+        .chars()
+        .map(|c| match c {
+            '\u{201C}' | '\u{201D}' | '\u{201E}' | '\u{201F}' => {
+                count += 1;
+                '"'
+            }
+            '\u{2018}' | '\u{2019}' | '\u{201A}' | '\u{201B}' => {
+                count += 1;
+                '\''
+            }
+            c => c,
+        })
+        .collect();
+    (normalized, count)
 }
 
-fn is_valid_email(s: &str) -> bool {
-    let re = Regex::new(r"^[^\s@]+@[^\s@]+\.[^\s@]+$").unwrap();
-    re.is_match(s)
+/// Lists the top-level fields of `data`, tagging the read-only ones.
+fn print_fields(data: &Value, read_only: &[String]) {
+    let fields: Vec<String> = match data {
+        Value::Object(obj) => obj.keys().map(|key| format!(".{}", key)).collect(),
+        // With --each, list every element's fields under its index
+        Value::Array(arr) => arr
+            .iter()
+            .enumerate()
+            .filter_map(|(i, element)| Some((i, element.as_object()?)))
+            .flat_map(|(i, obj)| obj.keys().map(move |key| format!("[{}].{}", i, key)))
+            .collect(),
+        _ => return,
+    };
+    println!("Fields:");
+    for path in &fields {
+        if read_only.contains(path) {
+            println!("  {} (read-only)", path);
+        } else {
+            println!("  {}", path);
+        }
+    }
+    // Nested read-only fields aren't covered by the listing above
+    for path in read_only.iter().filter(|p| !fields.contains(p)) {
+        println!("  {} (read-only)", path);
+    }
 }
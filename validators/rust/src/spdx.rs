@@ -0,0 +1,185 @@
+//! SPDX license identifiers and expressions, for the `spdx` and `spdx-id`
+//! formats.
+
+/// License identifiers, including deprecated ones, one per line.
+const LICENSES: &str = include_str!("spdx/licenses.txt");
+/// Exception identifiers usable after `WITH`, one per line.
+const EXCEPTIONS: &str = include_str!("spdx/exceptions.txt");
+
+fn listed(list: &str, id: &str) -> bool {
+    list.lines()
+        .filter(|line| !line.starts_with('#'))
+        .any(|line| line == id)
+}
+
+/// The listed spelling of `id`, looked up ignoring case.
+fn canonical(list: &'static str, id: &str) -> Option<&'static str> {
+    list.lines()
+        .filter(|line| !line.starts_with('#'))
+        .find(|line| line.eq_ignore_ascii_case(id))
+}
+
+/// Whether `id` is a license identifier: a listed one, spelled as listed and
+/// optionally followed by `+` for "or later", or a user-defined
+/// `LicenseRef-` / `DocumentRef-...:LicenseRef-` reference. Matching is
+/// case-sensitive even though SPDX allows otherwise, so that `mit` is
+/// reported and can be fixed to `MIT`.
+pub(crate) fn is_license_id(id: &str) -> bool {
+    let reference = id.split_once(':').map_or(id, |(document, reference)| {
+        if has_prefix(document, "DocumentRef-") {
+            reference
+        } else {
+            id
+        }
+    });
+    if has_prefix(reference, "LicenseRef-") {
+        return is_idstring(&reference["LicenseRef-".len()..]);
+    }
+    listed(LICENSES, id.strip_suffix('+').unwrap_or(id))
+}
+
+/// The correctly cased spelling of a miscased license identifier such as
+/// `apache-2.0`, keeping a trailing `+`.
+pub(crate) fn canonical_license(id: &str) -> Option<String> {
+    if is_license_id(id) {
+        return None;
+    }
+    let (base, plus) = id.strip_suffix('+').map_or((id, ""), |base| (base, "+"));
+    canonical(LICENSES, base).map(|listed| format!("{}{}", listed, plus))
+}
+
+/// `expression` with each miscased license and exception identifier
+/// spelled as listed, when that makes it valid.
+pub(crate) fn fix_case(expression: &str) -> Option<String> {
+    let mut fixed = String::with_capacity(expression.len());
+    let mut rest = expression;
+    while let Some(c) = rest.chars().next() {
+        let end = rest
+            .find(|c: char| c.is_whitespace() || c == '(' || c == ')')
+            .unwrap_or(rest.len());
+        if end == 0 {
+            fixed.push(c);
+            rest = &rest[c.len_utf8()..];
+            continue;
+        }
+        let word = &rest[..end];
+        match canonical_license(word) {
+            Some(license) => fixed.push_str(&license),
+            None => fixed.push_str(canonical(EXCEPTIONS, word).unwrap_or(word)),
+        }
+        rest = &rest[end..];
+    }
+    (fixed != expression && check_expression(&fixed).is_ok()).then_some(fixed)
+}
+
+fn has_prefix(s: &str, prefix: &str) -> bool {
+    s.get(..prefix.len())
+        .is_some_and(|p| p.eq_ignore_ascii_case(prefix))
+}
+
+fn is_idstring(s: &str) -> bool {
+    !s.is_empty()
+        && s.chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '.')
+}
+
+/// Checks an SPDX license expression such as `MIT OR Apache-2.0` or
+/// `(GPL-2.0-only WITH Classpath-exception-2.0)`. On failure, returns why,
+/// naming the first unrecognized token.
+pub(crate) fn check_expression(expression: &str) -> Result<(), String> {
+    let spaced = expression.replace('(', " ( ").replace(')', " ) ");
+    let tokens: Vec<&str> = spaced.split_whitespace().collect();
+    if tokens.is_empty() {
+        return Err("empty license expression".to_string());
+    }
+    let mut parser = Parser { tokens, pos: 0 };
+    parser.or_expression()?;
+    match parser.peek() {
+        None => Ok(()),
+        Some(token) => Err(format!("unexpected \"{}\"", token)),
+    }
+}
+
+/// Recursive descent over `or := and (OR and)*`, `and := with (AND with)*`,
+/// `with := atom (WITH exception)?` and `atom := license | ( or )`.
+struct Parser<'t> {
+    tokens: Vec<&'t str>,
+    pos: usize,
+}
+
+impl<'t> Parser<'t> {
+    fn peek(&self) -> Option<&'t str> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    /// Consumes the operator `op`, which SPDX allows in upper or lower case.
+    fn eat(&mut self, op: &str) -> bool {
+        let matched = self
+            .peek()
+            .is_some_and(|t| t == op || t == op.to_lowercase());
+        self.pos += usize::from(matched);
+        matched
+    }
+
+    fn or_expression(&mut self) -> Result<(), String> {
+        self.and_expression()?;
+        while self.eat("OR") {
+            self.and_expression()?;
+        }
+        Ok(())
+    }
+
+    fn and_expression(&mut self) -> Result<(), String> {
+        self.with_expression()?;
+        while self.eat("AND") {
+            self.with_expression()?;
+        }
+        Ok(())
+    }
+
+    fn with_expression(&mut self) -> Result<(), String> {
+        self.atom()?;
+        if self.eat("WITH") {
+            match self.peek() {
+                Some(exception) if listed(EXCEPTIONS, exception) => self.pos += 1,
+                Some(token) => {
+                    return Err(match canonical(EXCEPTIONS, token) {
+                        Some(exception) => format!(
+                            "unknown license exception \"{}\" (did you mean \"{}\"?)",
+                            token, exception
+                        ),
+                        None => format!("unknown license exception \"{}\"", token),
+                    })
+                }
+                None => return Err("expected an exception after WITH".to_string()),
+            }
+        }
+        Ok(())
+    }
+
+    fn atom(&mut self) -> Result<(), String> {
+        match self.peek() {
+            Some("(") => {
+                self.pos += 1;
+                self.or_expression()?;
+                if self.peek() != Some(")") {
+                    return Err("unclosed \"(\"".to_string());
+                }
+                self.pos += 1;
+                Ok(())
+            }
+            Some(token) if is_license_id(token) => {
+                self.pos += 1;
+                Ok(())
+            }
+            Some(token) => match canonical_license(token) {
+                Some(license) => Err(format!(
+                    "unknown license \"{}\" (did you mean \"{}\"?)",
+                    token, license
+                )),
+                None => Err(format!("unknown license \"{}\"", token)),
+            },
+            None => Err("expected a license".to_string()),
+        }
+    }
+}
@@ -0,0 +1,53 @@
+//! `wasm-bindgen` exports for web playgrounds and browser-hosted editors.
+
+use crate::{parse_jsonc, Severity, ValidationError, Validator};
+use serde::Serialize;
+use std::path::Path;
+use wasm_bindgen::prelude::*;
+
+/// What `validate_str` returns: the errors, and the warnings as the CLI
+/// prints them.
+#[derive(Serialize)]
+struct Outcome {
+    errors: Vec<ValidationError>,
+    warnings: Vec<String>,
+}
+
+/// Validates raw `.gitinfo` text (JSONC allowed) against a schema, returning
+/// `{ errors, warnings }`: the errors as `{ path, keyword, message }`
+/// objects, and failures under `x-severity: "warning"` among the warnings.
+/// A schema or text that doesn't parse yields a single `schema` or `parse`
+/// error. External `$ref`s can't be resolved without a filesystem and are
+/// reported as errors.
+#[wasm_bindgen]
+pub fn validate_str(data_json: &str, schema_json: &str) -> JsValue {
+    let outcome = match serde_json::from_str(schema_json) {
+        Err(e) => Outcome {
+            errors: vec![ValidationError::new(
+                "",
+                "schema",
+                format!("invalid schema: {}", e),
+            )],
+            warnings: Vec::new(),
+        },
+        Ok(schema) => match parse_jsonc(data_json) {
+            Err(e) => Outcome {
+                errors: vec![ValidationError::new("", "parse", e.to_string())],
+                warnings: Vec::new(),
+            },
+            Ok(data) => {
+                let mut validator = Validator::new(&schema, Path::new(""));
+                validator.validate(&data);
+                // x-severity "warning" failures are only warnings, as in the CLI
+                let (downgraded, errors): (Vec<ValidationError>, Vec<ValidationError>) = validator
+                    .errors
+                    .into_iter()
+                    .partition(|e| e.severity == Severity::Warning);
+                let mut warnings = validator.warnings;
+                warnings.extend(downgraded.iter().map(|e| e.to_string()));
+                Outcome { errors, warnings }
+            }
+        },
+    };
+    serde_wasm_bindgen::to_value(&outcome).expect("outcome serializes")
+}